@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::metadata_cache::MetadataCache;
+use crate::settings::AppSettings;
+use crate::{collect_image_files, get_auto_session_file, read_image_file_internal, SessionData};
+
+// Give the window and the first frontend requests a head start before warming begins
+const WARM_START_DELAY: Duration = Duration::from_secs(3);
+
+// Upper bound on images touched per launch, so a huge last folder doesn't keep the disk busy for minutes
+const WARM_MAX_IMAGES: usize = 2000;
+
+// Helper function to collect the images worth warming: the last autosaved session's tabs, then the last folder
+fn warm_targets(settings: &AppSettings) -> Vec<String> {
+    let mut paths = Vec::new();
+
+    let session = get_auto_session_file(None)
+        .ok()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|json_data| serde_json::from_str::<SessionData>(&json_data).ok());
+    if let Some(session) = session {
+        paths.extend(session.tabs.into_iter().map(|tab| tab.image_path));
+    }
+
+    if let Some(folder) = settings.last_folder.as_deref().filter(|folder| Path::new(folder).is_dir()) {
+        match collect_image_files(Path::new(folder)) {
+            Ok(entries) => paths.extend(entries.into_iter().map(|entry| entry.path)),
+            Err(e) => eprintln!("Warning: Failed to list last folder for cache warming: {}", e),
+        }
+    }
+
+    // A tab's image usually also sits in the last folder
+    let mut seen = HashSet::new();
+    paths.retain(|path| seen.insert(path.clone()));
+    paths.truncate(WARM_MAX_IMAGES);
+    paths
+}
+
+/// Pre-read metadata for recently used images in the background.
+/// Images are read one at a time through the decode pool, so user requests are never starved.
+pub fn spawn_cache_warming(settings: AppSettings, cache: Arc<MetadataCache>, decode_permits: Arc<Semaphore>) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WARM_START_DELAY).await;
+
+        let paths = tokio::task::spawn_blocking(move || warm_targets(&settings))
+            .await
+            .unwrap_or_default();
+
+        let mut warmed = 0;
+        for path in &paths {
+            // Failures (moved files, corrupt images) are expected here and reported when actually opened
+            if read_image_file_internal(path, &cache, &decode_permits, u64::MAX, true).await.is_ok() {
+                warmed += 1;
+            }
+        }
+
+        println!("Warmed metadata cache for {} of {} recent images", warmed, paths.len());
+    });
+}
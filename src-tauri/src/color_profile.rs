@@ -0,0 +1,61 @@
+use image::codecs::jpeg::JpegDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::tiff::TiffDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{ImageDecoder, ImageFormat};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Read the embedded ICC profile of an image, if the format supports one and it is present.
+/// Only the header/metadata is parsed; pixel data is not decoded.
+pub fn read_icc_profile(path: &Path, format: ImageFormat) -> Option<Vec<u8>> {
+    let reader = BufReader::new(File::open(path).ok()?);
+
+    match format {
+        ImageFormat::Png => PngDecoder::new(reader).ok()?.icc_profile(),
+        ImageFormat::Jpeg => JpegDecoder::new(reader).ok()?.icc_profile(),
+        ImageFormat::Tiff => TiffDecoder::new(reader).ok()?.icc_profile(),
+        ImageFormat::WebP => WebPDecoder::new(reader).ok()?.icc_profile(),
+        _ => None,
+    }
+}
+
+// Helper function to read a big-endian u32 at `offset`
+fn read_u32(bytes: &[u8], offset: usize) -> Option<usize> {
+    let slice = bytes.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([slice[0], slice[1], slice[2], slice[3]]) as usize)
+}
+
+/// Extract the human-readable description (`desc` tag) of an ICC profile, e.g. "sRGB IEC61966-2.1".
+/// Handles both the ICC v2 `desc` text type and the v4 `mluc` multi-localized type (first record).
+pub fn profile_description(profile: &[u8]) -> Option<String> {
+    // Tag table follows the 128-byte header: count, then (signature, offset, size) triples
+    let tag_count = read_u32(profile, 128)?;
+    let tag_offset = (0..tag_count)
+        .map(|i| 132 + i * 12)
+        .find(|&entry| profile.get(entry..entry + 4) == Some(b"desc"))
+        .and_then(|entry| read_u32(profile, entry + 4))?;
+
+    let tag = profile.get(tag_offset..)?;
+    let description = match tag.get(0..4)? {
+        b"desc" => {
+            let length = read_u32(tag, 8)?;
+            let text = tag.get(12..12 + length)?;
+            String::from_utf8_lossy(text).trim_end_matches('\0').to_string()
+        }
+        b"mluc" => {
+            let length = read_u32(tag, 20)?;
+            let offset = read_u32(tag, 24)?;
+            let units: Vec<u16> = tag.get(offset..offset + length)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units).trim_end_matches('\0').to_string()
+        }
+        _ => return None,
+    };
+
+    let description = description.trim().to_string();
+    (!description.is_empty()).then_some(description)
+}
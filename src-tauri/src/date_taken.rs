@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::metadata_cache::MetadataCache;
+use crate::paths::long_path;
+use crate::{format_last_modified, FileEntry};
+
+/// Order of entries returned by the folder browse commands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortMode {
+    /// Natural filename order (the default)
+    Name,
+    /// EXIF DateTimeOriginal, oldest first, falling back to the modification time
+    DateTaken,
+}
+
+impl SortMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "name" => Ok(Self::Name),
+            "date_taken" => Ok(Self::DateTaken),
+            other => Err(format!("Unknown sort mode: {}", other)),
+        }
+    }
+}
+
+// Sort keys share one "YYYY-MM-DD HH:MM:SS" shape so EXIF dates and mtimes compare directly.
+// EXIF dates carry no time zone; they're compared as-is against UTC mtimes.
+const SORT_KEY_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Helper function to read EXIF DateTimeOriginal as a sort key
+fn read_date_taken(path: &Path) -> Option<String> {
+    let file = File::open(long_path(path)).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+    let field = exif.get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)?;
+
+    let exif::Value::Ascii(values) = &field.value else {
+        return None;
+    };
+    let date = exif::DateTime::from_ascii(values.first()?).ok()?;
+    Some(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        date.year, date.month, date.day, date.hour, date.minute, date.second,
+    ))
+}
+
+// Helper function to find an entry's sort key: the cached or freshly read EXIF date, else the mtime.
+// Unreadable files get an empty key and sort first, keeping their relative name order.
+fn date_taken_key(entry: &FileEntry, cache: &MetadataCache) -> String {
+    let path = Path::new(&entry.path);
+    let Ok(metadata) = fs::metadata(long_path(path)) else {
+        return String::new();
+    };
+    let (Ok(modified), Ok(last_modified)) = (metadata.modified(), format_last_modified(&metadata)) else {
+        return String::new();
+    };
+
+    let date_taken = match cache.get_date_taken(&entry.path, &last_modified) {
+        Ok(Some(cached)) => cached,
+        _ => {
+            let date_taken = read_date_taken(path);
+            if let Err(e) = cache.set_date_taken(&entry.path, &last_modified, date_taken.as_deref()) {
+                eprintln!("Warning: Failed to cache date taken for {}: {}", entry.path, e);
+            }
+            date_taken
+        }
+    };
+
+    date_taken.unwrap_or_else(|| DateTime::<Utc>::from(modified).format(SORT_KEY_FORMAT).to_string())
+}
+
+/// Sort entries by when they were taken. Entries arrive in name order and the sort is stable,
+/// so images with the same timestamp stay in name order.
+pub fn sort_by_date_taken(entries: Vec<FileEntry>, cache: &MetadataCache) -> Vec<FileEntry> {
+    let mut keyed: Vec<(String, FileEntry)> = entries.into_iter()
+        .map(|entry| (date_taken_key(&entry, cache), entry))
+        .collect();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+    keyed.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Apply a sort mode to entries already in name order
+pub fn apply_sort(entries: Vec<FileEntry>, sort: SortMode, cache: &MetadataCache) -> Vec<FileEntry> {
+    match sort {
+        SortMode::Name => entries,
+        SortMode::DateTaken => sort_by_date_taken(entries, cache),
+    }
+}
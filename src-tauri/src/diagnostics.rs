@@ -0,0 +1,118 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+use crate::paths::get_app_data_dir;
+use crate::{get_auto_session_file, AppState, SessionData};
+
+/// Exact versions for bug reports and the About box
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    app_version: String,
+    tauri_version: String,
+    webview_version: Option<String>, // None when the platform can't report it
+}
+
+/// Environment summary for the About/Diagnostics panel
+#[derive(Debug, Serialize)]
+pub struct Diagnostics {
+    app_version: String,
+    os: String,
+    arch: String,
+    app_data_dir: String,
+    app_data_size: u64,
+    cache_entry_count: Option<usize>,
+    cache_max_entries: Option<usize>,
+    recent_session_count: usize,
+    auto_session_exists: bool,
+    auto_session_valid: bool,
+}
+
+// Total size of all files below a directory (unreadable entries are ignored)
+fn directory_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[tauri::command]
+pub async fn get_diagnostics(state: State<'_, AppState>) -> Result<Diagnostics, String> {
+    let app_data_dir = get_app_data_dir()?;
+
+    let cache_stats = state.metadata_cache.get_stats().ok();
+
+    let auto_session_file = get_auto_session_file(None)?;
+    let auto_session_exists = auto_session_file.exists();
+    let auto_session_valid = auto_session_exists && fs::read_to_string(&auto_session_file)
+        .ok()
+        .map(|json_data| serde_json::from_str::<SessionData>(&json_data).is_ok())
+        .unwrap_or(false);
+
+    Ok(Diagnostics {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        app_data_size: directory_size(&app_data_dir),
+        app_data_dir: app_data_dir.to_string_lossy().to_string(),
+        cache_entry_count: cache_stats.as_ref().map(|stats| stats.entry_count),
+        cache_max_entries: cache_stats.as_ref().map(|stats| stats.max_entries),
+        recent_session_count: state.recent_sessions.lock().unwrap().len(),
+        auto_session_exists,
+        auto_session_valid,
+    })
+}
+
+#[tauri::command]
+pub async fn get_version_info() -> VersionInfo {
+    let webview_version = match tauri::webview_version() {
+        Ok(version) => Some(version),
+        Err(e) => {
+            eprintln!("Warning: Failed to query webview version: {}", e);
+            None
+        }
+    };
+
+    VersionInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        webview_version,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheIntegrityReport {
+    ok: bool,
+    result: String, // "ok", or SQLite's list of problems
+    recreated: bool,
+}
+
+// Run SQLite's integrity check on the metadata cache. A corrupt database is only
+// recreated when asked, since that also discards tags, ratings and seen flags.
+#[tauri::command]
+pub async fn check_cache_integrity(recreate_if_corrupt: Option<bool>, state: State<'_, AppState>) -> Result<CacheIntegrityReport, String> {
+    let result = state.metadata_cache.integrity_check()?;
+    let ok = result == "ok";
+
+    let mut recreated = false;
+    if !ok {
+        eprintln!("Metadata cache integrity check failed:\n{}", result);
+        if recreate_if_corrupt.unwrap_or(false) {
+            state.metadata_cache.recreate()?;
+            recreated = true;
+        } else {
+            eprintln!("Run check_cache_integrity with recreateIfCorrupt to rebuild the cache database");
+        }
+    }
+
+    Ok(CacheIntegrityReport { ok, result, recreated })
+}
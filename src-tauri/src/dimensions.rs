@@ -0,0 +1,79 @@
+use image::io::Reader as ImageReader;
+use image::ImageFormat;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::paths::describe_io_error;
+
+/// What a dimension reader learned about an image without decoding its pixels
+#[derive(Debug, Clone, Copy)]
+pub struct ProbedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: Option<ImageFormat>, // None for formats the `image` crate doesn't know (raw, HEIC, ...)
+}
+
+/// A way of measuring images. Readers are registered per extension, so a format the `image` crate
+/// handles poorly can get its own reader without touching read_image_file.
+pub trait DimensionReader: Send + Sync {
+    fn read_dimensions(&self, path: &Path) -> Result<ProbedImage, String>;
+}
+
+/// The default reader: the `image` crate, which identifies the format from the file's content
+pub struct ImageCrateReader;
+
+impl DimensionReader for ImageCrateReader {
+    fn read_dimensions(&self, path: &Path) -> Result<ProbedImage, String> {
+        let reader = ImageReader::open(path)
+            .map_err(|e| format!("Failed to open image file: {}", describe_io_error(&e, path)))?
+            .with_guessed_format()
+            .map_err(|e| format!("Failed to detect image format: {}", e))?;
+        let format = reader.format();
+        let (width, height) = reader.into_dimensions()
+            .map_err(|e| format!("Failed to read image dimensions: {}", e))?;
+
+        Ok(ProbedImage { width, height, format })
+    }
+}
+
+/// Dimension readers keyed by lowercase extension, with the `image` crate for everything else
+pub struct DimensionReaders {
+    by_extension: HashMap<&'static str, Box<dyn DimensionReader>>,
+    default: Box<dyn DimensionReader>,
+}
+
+impl DimensionReaders {
+    fn new() -> Self {
+        #[cfg_attr(not(feature = "raw"), allow(unused_mut))]
+        let mut readers = Self {
+            by_extension: HashMap::new(),
+            default: Box::new(ImageCrateReader),
+        };
+
+        #[cfg(feature = "raw")]
+        for (extension, _) in crate::raw::RAW_IMAGE_TYPES {
+            readers.register(extension, Box::new(crate::raw::RawDimensionReader));
+        }
+
+        readers
+    }
+
+    #[cfg_attr(not(feature = "raw"), allow(dead_code))]
+    fn register(&mut self, extension: &'static str, reader: Box<dyn DimensionReader>) {
+        self.by_extension.insert(extension, reader);
+    }
+
+    /// The reader for files with this (lowercase) extension
+    pub fn for_extension(&self, extension: &str) -> &dyn DimensionReader {
+        self.by_extension.get(extension)
+            .map(|reader| reader.as_ref())
+            .unwrap_or(self.default.as_ref())
+    }
+}
+
+/// The readers in use this run; built on first use from the enabled features
+pub fn dimension_readers() -> &'static DimensionReaders {
+    static READERS: OnceLock<DimensionReaders> = OnceLock::new();
+    READERS.get_or_init(DimensionReaders::new)
+}
@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::metadata_cache::MetadataCache;
+
+/// Force-exits the process when the frontend's exit save never finishes. Armed by begin_exit_save
+/// once the frontend has committed to closing; exit_app and cancel_exit disarm it.
+pub struct ExitWatchdog {
+    // Bumped on every arm and disarm, so a timer only fires if nothing happened since it started
+    generation: AtomicU64,
+}
+
+impl ExitWatchdog {
+    pub fn new() -> Self {
+        Self {
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Start a timer that exits the process after `timeout` unless disarmed first. Arming again
+    /// restarts the countdown. The metadata cache is flushed before exiting.
+    pub fn arm(self: &Arc<Self>, timeout: Duration, metadata_cache: Arc<MetadataCache>) {
+        self.arm_with(timeout, move || {
+            eprintln!("Forcing exit: the frontend did not finish closing within {:?}", timeout);
+            if let Err(e) = metadata_cache.flush() {
+                eprintln!("Warning: Failed to flush cache on forced exit: {}", e);
+            }
+            std::process::exit(0);
+        });
+    }
+
+    // Start a timer that runs `on_timeout` unless the watchdog is disarmed or re-armed first
+    fn arm_with(self: &Arc<Self>, timeout: Duration, on_timeout: impl FnOnce() + Send + 'static) {
+        let armed_generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let watchdog = self.clone();
+
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            if watchdog.generation.load(Ordering::SeqCst) != armed_generation {
+                return;
+            }
+            on_timeout();
+        });
+    }
+
+    /// Stop any running timer
+    pub fn disarm(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+    const WAIT: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn fires_when_left_armed() {
+        let watchdog = Arc::new(ExitWatchdog::new());
+        let (fired, rx) = mpsc::channel();
+
+        watchdog.arm_with(TIMEOUT, move || fired.send(()).unwrap());
+        assert!(rx.recv_timeout(WAIT).is_ok());
+    }
+
+    #[test]
+    fn disarm_stops_the_timer() {
+        let watchdog = Arc::new(ExitWatchdog::new());
+        let (fired, rx) = mpsc::channel();
+
+        watchdog.arm_with(TIMEOUT, move || fired.send(()).unwrap());
+        watchdog.disarm();
+        assert!(rx.recv_timeout(WAIT).is_err());
+    }
+
+    #[test]
+    fn rearming_replaces_the_earlier_timer() {
+        let watchdog = Arc::new(ExitWatchdog::new());
+        let (fired, rx) = mpsc::channel();
+
+        let first = fired.clone();
+        watchdog.arm_with(TIMEOUT, move || first.send("first").unwrap());
+        watchdog.arm_with(TIMEOUT * 2, move || fired.send("second").unwrap());
+        assert_eq!(rx.recv_timeout(WAIT), Ok("second"));
+        assert!(rx.recv_timeout(WAIT).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::paths::sanitize_filename;
+use crate::AppState;
+
+/// How many file operations are remembered for undo
+const MAX_UNDO_ENTRIES: usize = 20;
+
+/// A file mutation performed by the app, with enough detail to reverse it
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FileOperation {
+    Rename { from: String, to: String },
+    Move { from: String, to: String },
+    Trash { path: String },
+}
+
+/// Payload of the file mutation events, emitted once per affected file by every command that
+/// renames, moves, deletes or restores an image:
+///
+/// - `file-renamed`: `from` was renamed to `to` in the same directory
+/// - `file-moved`: `from` was moved to `to` in another directory
+/// - `file-deleted`: `from` was moved to the trash; `to` is None
+/// - `file-restored`: `to` came back from the trash (undo); `from` is the same path
+///
+/// Events fire only after the file operation succeeded and the metadata cache was updated,
+/// so a listener that re-reads the file immediately sees the new cache rows.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMutationEvent {
+    from: String,
+    to: Option<String>,
+}
+
+// Helper function to announce a completed file mutation (see FileMutationEvent for the contract)
+fn emit_file_mutation(app: &AppHandle, event: &str, from: &str, to: Option<&str>) {
+    let payload = FileMutationEvent {
+        from: from.to_string(),
+        to: to.map(str::to_string),
+    };
+    if let Err(e) = app.emit(event, payload) {
+        eprintln!("Warning: Failed to emit {} for {}: {}", event, from, e);
+    }
+}
+
+/// Per-file outcome of a bulk delete: (path, result)
+pub type DeleteResult = (String, Result<(), String>);
+
+/// One file of a batch rename: (old path, new path)
+pub type RenameMapping = (String, String);
+
+// Batch rename indexes are padded to at least this many digits (`001`)
+const MIN_INDEX_DIGITS: usize = 3;
+
+// Helper function to remember an operation, dropping the oldest once the log is full
+fn record_operation(state: &AppState, operation: FileOperation) {
+    let mut log = state.file_operations.lock().unwrap();
+    log.push(operation);
+    if log.len() > MAX_UNDO_ENTRIES {
+        log.remove(0);
+    }
+}
+
+// Helper function to move a file, falling back to copy + delete across filesystems
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if to.exists() {
+        return Err(format!("A file already exists at: {}", to.display()));
+    }
+
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)
+            .map_err(|e| format!("Failed to copy file: {}", e))?;
+        fs::remove_file(from)
+            .map_err(|e| format!("Failed to remove original file: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// Helper function to put a trashed file back at its original location
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+fn restore_from_trash(path: &str) -> Result<(), String> {
+    use trash::os_limited;
+
+    let items = os_limited::list()
+        .map_err(|e| format!("Failed to list trash contents: {}", e))?;
+
+    // The same path may have been trashed more than once; restore the newest copy
+    let item = items
+        .into_iter()
+        .filter(|item| item.original_path() == Path::new(path))
+        .max_by_key(|item| item.time_deleted)
+        .ok_or_else(|| format!("File is no longer in the trash: {}", path))?;
+
+    os_limited::restore_all([item])
+        .map_err(|e| format!("Failed to restore from trash: {}", e))
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+fn restore_from_trash(_path: &str) -> Result<(), String> {
+    Err("Restoring from the trash is not supported on this platform".to_string())
+}
+
+// Helper function to move an image and keep its cache row attached to the new path
+fn relocate_image(from: &Path, to: &Path, state: &AppState) -> Result<String, String> {
+    if !from.is_file() {
+        return Err(format!("Image file does not exist: {}", from.display()));
+    }
+
+    move_file(from, to)?;
+
+    let old_path = from.to_string_lossy().to_string();
+    let new_path = to.to_string_lossy().to_string();
+    if let Err(e) = state.metadata_cache.rename(&old_path, &new_path) {
+        eprintln!("Warning: Failed to update cache after moving {}: {}", old_path, e);
+    }
+
+    println!("Moved image: {} -> {}", old_path, new_path);
+    Ok(new_path)
+}
+
+#[tauri::command]
+pub async fn rename_image(app: AppHandle, path: String, new_name: String, state: State<'_, AppState>) -> Result<String, String> {
+    let from = PathBuf::from(&path);
+
+    let new_name = sanitize_filename(&new_name);
+    if new_name.is_empty() {
+        return Err("Invalid file name".to_string());
+    }
+
+    let to = from.with_file_name(new_name);
+    let new_path = relocate_image(&from, &to, &state)?;
+
+    emit_file_mutation(&app, "file-renamed", &path, Some(&new_path));
+    record_operation(&state, FileOperation::Rename { from: path, to: new_path.clone() });
+    Ok(new_path)
+}
+
+// Helper function to fill in a batch rename pattern for one file
+fn expand_rename_pattern(pattern: &str, path: &Path, index: String) -> Result<String, String> {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let extension = path.extension().map(|ext| ext.to_string_lossy()).unwrap_or_default();
+
+    let name = sanitize_filename(&pattern
+        .replace("{n}", &index)
+        .replace("{name}", &stem)
+        .replace("{ext}", &extension));
+    if name.is_empty() {
+        return Err(format!("Pattern gives an invalid file name for: {}", path.display()));
+    }
+    Ok(name)
+}
+
+// Helper function to work out every new path and reject the batch if any of them would collide.
+// Names are compared case-insensitively since Windows and macOS filesystems usually are.
+fn plan_batch_rename(paths: &[String], pattern: &str, start_index: u32) -> Result<Vec<RenameMapping>, String> {
+    let last_index = start_index as u64 + paths.len().saturating_sub(1) as u64;
+    let digits = last_index.to_string().len().max(MIN_INDEX_DIGITS);
+
+    let mut planned = Vec::with_capacity(paths.len());
+    let mut targets = HashSet::new();
+    for (offset, path) in paths.iter().enumerate() {
+        let from = Path::new(path);
+        if !from.is_file() {
+            return Err(format!("Image file does not exist: {}", path));
+        }
+
+        let index = format!("{:0width$}", start_index as u64 + offset as u64, width = digits);
+        let to = from.with_file_name(expand_rename_pattern(pattern, from, index)?);
+        let new_path = to.to_string_lossy().to_string();
+
+        if !targets.insert(new_path.to_lowercase()) {
+            return Err(format!("Collision: more than one file would be renamed to {}", new_path));
+        }
+        // The file itself (unchanged, or differing only in case) isn't a collision
+        if to.exists() && !new_path.eq_ignore_ascii_case(path) {
+            return Err(format!("Collision: {} already exists", new_path));
+        }
+
+        planned.push((path.clone(), new_path));
+    }
+
+    Ok(planned)
+}
+
+// Rename images by pattern: `{n}` is the zero-padded index, `{name}` the original stem and `{ext}`
+// the original extension. Every file stays in its own directory. All new names are checked
+// before anything is renamed; existing files count as collisions even if they're part of the batch.
+#[tauri::command]
+pub async fn batch_rename(
+    app: AppHandle,
+    paths: Vec<String>,
+    pattern: String,
+    start_index: u32,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<RenameMapping>, String> {
+    let planned = plan_batch_rename(&paths, &pattern, start_index)?;
+    if dry_run.unwrap_or(false) {
+        return Ok(planned);
+    }
+
+    let mut renamed = Vec::with_capacity(planned.len());
+    for (from, to) in planned {
+        if from == to {
+            renamed.push((from, to));
+            continue;
+        }
+
+        // Files renamed so far stay renamed; each one is in the undo log
+        if let Err(e) = relocate_image(Path::new(&from), Path::new(&to), &state) {
+            return Err(format!("Failed to rename {} after renaming {} files: {}", from, renamed.len(), e));
+        }
+        emit_file_mutation(&app, "file-renamed", &from, Some(&to));
+        record_operation(&state, FileOperation::Rename { from: from.clone(), to: to.clone() });
+        renamed.push((from, to));
+    }
+
+    println!("Batch renamed {} images", renamed.len());
+
+    Ok(renamed)
+}
+
+#[tauri::command]
+pub async fn move_image(app: AppHandle, path: String, dest_dir: String, state: State<'_, AppState>) -> Result<String, String> {
+    let from = PathBuf::from(&path);
+    let dest_dir = PathBuf::from(&dest_dir);
+
+    if !dest_dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", dest_dir.display()));
+    }
+
+    let file_name = from.file_name()
+        .ok_or_else(|| format!("Invalid image path: {}", path))?;
+
+    let new_path = relocate_image(&from, &dest_dir.join(file_name), &state)?;
+
+    emit_file_mutation(&app, "file-moved", &path, Some(&new_path));
+    record_operation(&state, FileOperation::Move { from: path, to: new_path.clone() });
+    Ok(new_path)
+}
+
+#[tauri::command]
+pub async fn undo_last_file_operation(app: AppHandle, state: State<'_, AppState>) -> Result<Option<FileOperation>, String> {
+    let operation = match state.file_operations.lock().unwrap().pop() {
+        Some(operation) => operation,
+        None => return Ok(None),
+    };
+
+    let result = match &operation {
+        FileOperation::Rename { from, to } | FileOperation::Move { from, to } => {
+            relocate_image(Path::new(to), Path::new(from), &state).map(|_| ())
+        }
+        FileOperation::Trash { path } => restore_from_trash(path),
+    };
+
+    if let Err(e) = result {
+        // Keep the entry so the user can retry once the conflict is resolved
+        state.file_operations.lock().unwrap().push(operation);
+        return Err(format!("Failed to undo file operation: {}", e));
+    }
+
+    // Undoing is itself a mutation, reported in the reverse direction
+    match &operation {
+        FileOperation::Rename { from, to } => emit_file_mutation(&app, "file-renamed", to, Some(from)),
+        FileOperation::Move { from, to } => emit_file_mutation(&app, "file-moved", to, Some(from)),
+        FileOperation::Trash { path } => emit_file_mutation(&app, "file-restored", path, Some(path)),
+    }
+
+    println!("Undid file operation: {:?}", operation);
+    Ok(Some(operation))
+}
+
+#[tauri::command]
+pub async fn delete_images(
+    app: AppHandle,
+    paths: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<DeleteResult>, String> {
+    let mut results = Vec::with_capacity(paths.len());
+    let mut deleted = 0;
+
+    // Keep going after failures so one locked file doesn't block the rest of the batch
+    for path in paths {
+        let result = if Path::new(&path).is_file() {
+            trash::delete(&path).map_err(|e| format!("Failed to move to trash: {}", e))
+        } else {
+            Err(format!("Image file does not exist: {}", path))
+        };
+
+        if result.is_ok() {
+            if let Err(e) = state.metadata_cache.remove(&path) {
+                eprintln!("Warning: Failed to update cache after deleting {}: {}", path, e);
+            }
+            emit_file_mutation(&app, "file-deleted", &path, None);
+            record_operation(&state, FileOperation::Trash { path: path.clone() });
+            deleted += 1;
+        }
+
+        results.push((path, result));
+    }
+
+    println!("Moved {} images to trash", deleted);
+
+    Ok(results)
+}
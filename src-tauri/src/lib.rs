@@ -1,10 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc}; // Still needed for read_image_file
 use image::io::Reader as ImageReader;
 use uuid::Uuid;
-use natord;
 use tauri::{
     Emitter,
     Manager,
@@ -12,24 +12,62 @@ use tauri::{
     menu::{MenuBuilder, SubmenuBuilder, PredefinedMenuItem},
 };
 use std::sync::{Arc, Mutex};
-
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+mod cache_warming;
+mod color_profile;
+mod date_taken;
+mod diagnostics;
+mod dimensions;
+mod exit_watchdog;
+mod file_ops;
+mod menu_action;
 mod metadata_cache;
-use metadata_cache::MetadataCache;
+mod operations;
+mod path_allowlist;
+mod paths;
+mod preview;
+#[cfg(feature = "raw")]
+mod raw;
+mod session_paths;
+mod session_watcher;
+mod settings;
+mod shell;
+mod tags;
+mod text_metadata;
+use date_taken::{apply_sort, SortMode};
+use dimensions::{dimension_readers, ProbedImage};
+use exit_watchdog::ExitWatchdog;
+use menu_action::MenuAction;
+use metadata_cache::{CachedMetadata, MetadataCache};
+use operations::OperationRegistry;
+use path_allowlist::{is_path_allowed, PathAllowlist};
+use paths::{canonicalize_path, describe_io_error, get_app_data_dir, long_path, same_path, sanitize_filename, write_atomic};
+use session_watcher::SessionWatcher;
+use settings::{update_settings, AppSettings};
 
 // Struct to track currently loaded session information
 #[derive(Debug, Clone)]
 struct LoadedSessionInfo {
     name: String,
-    #[allow(dead_code)]
-    path: String, // Used for reload functionality, but not directly accessed in Rust
+    path: String, // Used for reload functionality and watched for external changes
 }
 
 // Application state to track if we're in the process of exiting
 struct AppState {
     is_exiting: Arc<Mutex<bool>>,
+    exit_watchdog: Arc<ExitWatchdog>, // Force-exits if the frontend hangs between begin_exit_save and exit_app
     metadata_cache: Arc<MetadataCache>,
-    recent_sessions: Arc<Mutex<Vec<String>>>, // Stores paths to recent manual sessions
+    recent_sessions: Arc<Mutex<Vec<RecentSession>>>, // Recent manual sessions, most recently opened first
+    recent_sessions_load_status: RecentSessionsLoadStatus, // Whether startup had to reset a corrupt recent list
     loaded_session: Arc<Mutex<Option<LoadedSessionInfo>>>, // Currently loaded session
+    settings: Arc<Mutex<AppSettings>>, // User preferences persisted in settings.json
+    session_watcher: Arc<SessionWatcher>, // Reports external edits to the loaded session file
+    active_image: Arc<Mutex<Option<String>>>, // File name of the image shown in the active tab
+    file_operations: Arc<Mutex<Vec<file_ops::FileOperation>>>, // Recent file mutations, newest last, for undo
+    operations: Arc<OperationRegistry>, // Cancellation flags for long-running commands
+    decode_permits: Arc<Semaphore>, // Bounds concurrent decodes so bursts of requests queue instead of thrashing
+    allowed_paths: Arc<PathAllowlist>, // Folders opened via dialogs, browsing or sessions; reads outside them are refused
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +78,8 @@ pub struct FileEntry {
     is_image: bool,
     size: Option<u64>,
     last_modified: Option<String>,
+    is_symlink: bool,
+    symlink_target: Option<String>, // Resolved link target; None for regular entries and broken links
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,6 +91,16 @@ pub struct ImageData {
     dimensions: ImageDimensions,
     file_size: u64,
     last_modified: String,
+    format: String,
+    has_icc_profile: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icc_profile_name: Option<String>,
+    rating: u8,
+    pixel_count: u64,
+    large_image: bool, // pixel_count exceeds the configured threshold; the UI may warn or downscale
+    preview_url: Option<String>, // Downscaled copy for oversized images; asset_url stays the original
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data_url: Option<String>, // Inline base64 copy for environments without the asset protocol (opt-in)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -126,6 +176,8 @@ pub struct PaginatedFolderResult {
     has_more: bool,
     offset: usize,
     limit: usize,
+    total_bytes: Option<u64>, // Only computed when requested (requires a stat per file)
+    signature: String, // Changes when the folder's listing does; unaffected by filters, sort and paging
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -134,317 +186,1365 @@ pub struct LoadedSessionResult {
     session_data: SessionData,
     path: String,
     name: String,
+    #[serde(rename = "imageErrors", default)]
+    image_errors: Vec<SessionImageError>, // Only filled when the load was asked to resolve images
+}
+
+// One tab whose image couldn't be read while loading a session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionImageError {
+    #[serde(rename = "tabId")]
+    tab_id: String,
+    path: String,
+    error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEvictedEvent {
+    removed: usize,
+    entry_count: usize,
+}
+
+// Helper function to describe a possible symlink without following it: (is_symlink, resolved target).
+// A broken link still counts as a symlink but has no target, so it's listed rather than dropped.
+fn symlink_info(path: &Path) -> (bool, Option<String>) {
+    let is_symlink = fs::symlink_metadata(long_path(path))
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if !is_symlink {
+        return (false, None);
+    }
+
+    let target = dunce::canonicalize(path)
+        .ok()
+        .map(|target| target.to_string_lossy().to_string());
+    (true, target)
+}
+
+// Helper function to turn a directory entry into an image FileEntry (None for directories and non-images)
+fn image_entry_from_dir_entry(dir_entry: &fs::DirEntry, supported_extensions: &[String]) -> Option<FileEntry> {
+    // Skip directories entirely - only process files
+    if let Ok(file_type) = dir_entry.file_type() {
+        if file_type.is_dir() {
+            return None;
+        }
+    }
+
+    let path = dir_entry.path();
+
+    // Only include files with supported image extensions
+    let is_image = path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| supported_extensions.contains(&ext.to_lowercase()))
+        .unwrap_or(false);
+
+    if !is_image {
+        return None;
+    }
+
+    let name = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Unknown")
+        .to_string();
+    let (is_symlink, symlink_target) = symlink_info(&path);
+
+    Some(FileEntry {
+        name,
+        path: path.to_string_lossy().to_string(),
+        is_directory: false,
+        is_image: true,
+        size: None,
+        last_modified: None,
+        is_symlink,
+        symlink_target,
+    })
+}
+
+// Helper function to tell whether a file or folder is hidden. "Hidden" means the same on every
+// platform: a name starting with `.` (the macOS/Linux convention, which also catches `.thumbnail.png`
+// style junk copied onto Windows drives), plus, on Windows only, the hidden file attribute.
+// The macOS Finder "hidden" flag is not checked.
+fn is_hidden(path: &Path) -> bool {
+    let dot_named = path.file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(false);
+    if dot_named {
+        return true;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if let Ok(metadata) = fs::metadata(long_path(path)) {
+            return metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0;
+        }
+    }
+
+    false
 }
 
 // Helper function to collect image files from a directory
 fn collect_image_files(target_path: &Path) -> Result<Vec<FileEntry>, String> {
-    let mut entries = Vec::new();
     let supported_extensions = get_supported_image_extensions();
 
-    match fs::read_dir(target_path) {
-        Ok(dir_entries) => {
-            for entry in dir_entries {
-                if let Ok(dir_entry) = entry {
-                    // Skip directories entirely - only process files
-                    if let Ok(file_type) = dir_entry.file_type() {
-                        if file_type.is_dir() {
-                            continue;
-                        }
-                    }
+    let mut entries: Vec<FileEntry> = match fs::read_dir(long_path(target_path)) {
+        Ok(dir_entries) => dir_entries
+            .flatten()
+            .filter_map(|dir_entry| image_entry_from_dir_entry(&dir_entry, &supported_extensions))
+            .collect(),
+        Err(e) => return Err(format!("Failed to read directory: {}", describe_io_error(&e, target_path))),
+    };
 
-                    let path = dir_entry.path();
+    // Sort entries using natural sort for consistent ordering (handles numeric portions correctly)
+    entries.sort_by(|a, b| natord::compare_ignore_case(&a.name, &b.name));
 
-                    // Only include files with supported image extensions
-                    let is_image = path.extension()
-                        .and_then(|ext| ext.to_str())
-                        .map(|ext| supported_extensions.contains(&ext.to_lowercase()))
-                        .unwrap_or(false);
+    Ok(entries)
+}
 
-                    if !is_image {
-                        continue;
-                    }
+// Helper function to collect image files from a folder and all of its subfolders, in natural path order.
+// Symlinked folders are only descended into when `follow_symlinks` is set; otherwise they're listed
+// as directory entries so the UI can show them. Each real folder is scanned once, so links pointing
+// back up the tree can't loop. Also returns the followed links, whose targets may lie outside `root`.
+fn collect_image_files_recursive(root: &Path, follow_symlinks: bool, show_hidden: bool) -> Result<(Vec<FileEntry>, Vec<PathBuf>), String> {
+    let supported_extensions = get_supported_image_extensions();
+    let mut entries = Vec::new();
+    let mut followed_links = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![root.to_path_buf()];
 
-                    let name = path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("Unknown")
-                        .to_string();
-
-                    entries.push(FileEntry {
-                        name: name.clone(),
-                        path: path.to_string_lossy().to_string(),
-                        is_directory: false,
-                        is_image: true,
-                        size: None,
-                        last_modified: None,
-                    });
-                }
+    while let Some(dir) = pending.pop() {
+        if !visited.insert(canonicalize_path(&dir)) {
+            continue;
+        }
+
+        let dir_entries = match fs::read_dir(long_path(&dir)) {
+            Ok(dir_entries) => dir_entries,
+            Err(e) if dir == root => return Err(format!("Failed to read directory: {}", describe_io_error(&e, root))),
+            // An unreadable subfolder shouldn't hide the rest of the tree
+            Err(e) => {
+                eprintln!("Warning: Skipping unreadable folder {}: {}", dir.display(), describe_io_error(&e, &dir));
+                continue;
+            }
+        };
+
+        for dir_entry in dir_entries.flatten() {
+            let path = dir_entry.path();
+            let Ok(file_type) = dir_entry.file_type() else {
+                continue;
+            };
+            let is_folder = file_type.is_dir() || (file_type.is_symlink() && path.is_dir());
+            if is_folder && !show_hidden && is_hidden(&path) {
+                continue;
+            }
+
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if is_folder && follow_symlinks {
+                followed_links.push(path.clone());
+                pending.push(path);
+            } else if is_folder {
+                let (is_symlink, symlink_target) = symlink_info(&path);
+                entries.push(FileEntry {
+                    name: dir_entry.file_name().to_string_lossy().to_string(),
+                    path: path.to_string_lossy().to_string(),
+                    is_directory: true,
+                    is_image: false,
+                    size: None,
+                    last_modified: None,
+                    is_symlink,
+                    symlink_target,
+                });
+            } else if let Some(entry) = image_entry_from_dir_entry(&dir_entry, &supported_extensions) {
+                entries.push(entry);
             }
         }
-        Err(e) => return Err(format!("Failed to read directory: {}", e)),
     }
 
-    // Sort entries using natural sort for consistent ordering (handles numeric portions correctly)
+    entries.sort_by(|a, b| natord::compare_ignore_case(&a.path, &b.path));
+
+    Ok((entries, followed_links))
+}
+
+// Helper function to identify a supported image by its leading bytes (magic numbers), ignoring the extension
+fn sniff_image_format(path: &Path) -> Option<image::ImageFormat> {
+    use std::io::Read;
+
+    let mut header = Vec::with_capacity(32);
+    fs::File::open(long_path(path)).ok()?
+        .take(32)
+        .read_to_end(&mut header)
+        .ok()?;
+    let format = image::guess_format(&header).ok()?;
+
+    let supported_extensions = get_supported_image_extensions();
+    format.extensions_str().iter()
+        .any(|ext| supported_extensions.iter().any(|supported| supported == ext))
+        .then_some(format)
+}
+
+// Helper function to collect image files by content rather than extension: extensionless
+// images are included and files that only look like images by name are dropped
+fn collect_image_files_by_content(target_path: &Path) -> Result<Vec<FileEntry>, String> {
+    let dir_entries = fs::read_dir(long_path(target_path))
+        .map_err(|e| format!("Failed to read directory: {}", describe_io_error(&e, target_path)))?;
+
+    let mut entries: Vec<FileEntry> = dir_entries
+        .flatten()
+        .filter(|dir_entry| dir_entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter(|dir_entry| sniff_image_format(&dir_entry.path()).is_some())
+        .map(|dir_entry| {
+            let (is_symlink, symlink_target) = symlink_info(&dir_entry.path());
+            FileEntry {
+                name: dir_entry.file_name().to_string_lossy().to_string(),
+                path: dir_entry.path().to_string_lossy().to_string(),
+                is_directory: false,
+                is_image: true,
+                size: None,
+                last_modified: None,
+                is_symlink,
+                symlink_target,
+            }
+        })
+        .collect();
+
     entries.sort_by(|a, b| natord::compare_ignore_case(&a.name, &b.name));
 
     Ok(entries)
 }
 
-// File system operations
-#[tauri::command]
-async fn browse_folder(path: Option<String>) -> Result<Vec<FileEntry>, String> {
-    let target_path = match path {
-        Some(p) => PathBuf::from(p),
-        None => std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?,
-    };
+// Helper function to fingerprint a folder's images from their names, sizes and modification times.
+// DefaultHasher isn't stable across Rust releases; callers caching it only pay for one extra re-browse.
+fn folder_signature(entries: &[FileEntry]) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::time::UNIX_EPOCH;
+
+    let mut files: Vec<(&str, u64, u128)> = entries.iter()
+        .map(|entry| {
+            let metadata = fs::metadata(long_path(Path::new(&entry.path))).ok();
+            let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+            let modified = metadata
+                .and_then(|m| m.modified().ok())
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_nanos())
+                .unwrap_or(0);
+            (entry.name.as_str(), size, modified)
+        })
+        .collect();
+    // Plain byte order, so the hash doesn't depend on the display sort
+    files.sort_unstable();
 
-    if !target_path.exists() {
-        return Err(format!("Path does not exist: {}", target_path.display()));
-    }
+    let mut hasher = DefaultHasher::new();
+    files.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// Helper function to fingerprint a folder cheaply from its own modification time and image count.
+// Adding, removing or renaming files changes it; editing a file in place doesn't (see folder_signature).
+fn quick_folder_signature(dir: &Path, image_count: usize) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::time::UNIX_EPOCH;
+
+    let modified = fs::metadata(long_path(dir))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+
+    let mut hasher = DefaultHasher::new();
+    (modified, image_count).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
 
+#[tauri::command]
+async fn get_folder_signature(path: String) -> Result<String, String> {
+    let target_path = PathBuf::from(&path);
     if !target_path.is_dir() {
-        return Err(format!("Path is not a directory: {}", target_path.display()));
+        return Err(format!("Path is not a directory: {}", path));
     }
 
-    collect_image_files(&target_path)
+    let entries = collect_image_files(&target_path)?;
+    Ok(folder_signature(&entries))
 }
 
+// File system operations
+// Each filter is a separate optional argument so the frontend can pass only what it needs
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
-async fn browse_folder_paginated(
+async fn browse_folder(
+    app_handle: tauri::AppHandle,
     path: Option<String>,
-    offset: Option<usize>,
-    limit: Option<usize>,
-) -> Result<PaginatedFolderResult, String> {
-    let target_path = match path {
-        Some(p) => PathBuf::from(p),
-        None => std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?,
+    aspect: Option<String>,
+    query: Option<String>,
+    min_rating: Option<u8>,
+    unseen_only: Option<bool>,
+    verify_content: Option<bool>,
+    sort: Option<String>,
+    show_hidden: Option<bool>,
+    recursive: Option<bool>,
+    follow_symlinks: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FileEntry>, String> {
+    let aspect = aspect.as_deref().map(AspectFilter::parse).transpose()?;
+    let sort = sort.as_deref().map(SortMode::parse).transpose()?.unwrap_or(SortMode::Name);
+    let target_path = resolve_browse_path(path)?;
+    let filter = ImageFilter {
+        query,
+        min_rating,
+        unseen_only: unseen_only.unwrap_or(false),
+        show_hidden: show_hidden.unwrap_or(false),
     };
 
-    if !target_path.exists() {
-        return Err(format!("Path does not exist: {}", target_path.display()));
+    // Sniffing opens every file, so it's only done on request, and never for a whole tree
+    let entries = if recursive.unwrap_or(false) {
+        // Symlinks aren't followed by default: they may lead to huge external volumes
+        let (entries, followed_links) = collect_image_files_recursive(&target_path, follow_symlinks.unwrap_or(false), filter.show_hidden)?;
+        for link in followed_links {
+            state.allowed_paths.allow(&link);
+        }
+        filter_and_sort(entries, sort, &filter, &state.metadata_cache)?
+    } else if verify_content.unwrap_or(false) {
+        filter_and_sort(collect_image_files_by_content(&target_path)?, sort, &filter, &state.metadata_cache)?
+    } else {
+        list_images(&target_path, sort, &filter, &state.metadata_cache)?
+    };
+    remember_last_folder(&target_path, &state);
+    state.allowed_paths.allow(&target_path);
+
+    // Runs last since it may decode every image; it keeps the sorted order
+    match aspect {
+        Some(aspect) => Ok(filter_by_aspect(&app_handle, &target_path, entries, aspect, &state.metadata_cache, &state.decode_permits).await),
+        None => Ok(entries),
     }
+}
+
+// List the immediate child directories of a folder, for the folder navigator
+#[tauri::command]
+async fn list_subfolders(path: String, show_hidden: Option<bool>) -> Result<Vec<FileEntry>, String> {
+    let target_path = Path::new(&path);
 
     if !target_path.is_dir() {
-        return Err(format!("Path is not a directory: {}", target_path.display()));
+        return Err(format!("Path is not a directory: {}", path));
     }
 
-    // Collect all image files
-    let all_entries = collect_image_files(&target_path)?;
-    let total_count = all_entries.len();
-
-    // Apply pagination
-    let offset = offset.unwrap_or(0);
-    let limit = limit.unwrap_or(500); // Default to 500 items per page
+    let show_hidden = show_hidden.unwrap_or(false);
+    let dir_entries = fs::read_dir(long_path(target_path))
+        .map_err(|e| format!("Failed to read directory: {}", describe_io_error(&e, target_path)))?;
+
+    let mut folders: Vec<FileEntry> = dir_entries
+        .flatten()
+        // file_type() doesn't follow links, so symlinked folders are checked through their target;
+        // links to files and broken links are left out
+        .filter(|dir_entry| match dir_entry.file_type() {
+            Ok(file_type) if file_type.is_symlink() => dir_entry.path().is_dir(),
+            Ok(file_type) => file_type.is_dir(),
+            Err(_) => false,
+        })
+        .filter(|dir_entry| show_hidden || !is_hidden(&dir_entry.path()))
+        .map(|dir_entry| {
+            let (is_symlink, symlink_target) = symlink_info(&dir_entry.path());
+            FileEntry {
+                name: dir_entry.file_name().to_string_lossy().to_string(),
+                path: dir_entry.path().to_string_lossy().to_string(),
+                is_directory: true,
+                is_image: false,
+                size: None,
+                last_modified: None,
+                is_symlink,
+                symlink_target,
+            }
+        })
+        .collect();
 
-    let end_index = std::cmp::min(offset + limit, total_count);
-    let entries: Vec<FileEntry> = if offset < total_count {
-        all_entries[offset..end_index].to_vec()
-    } else {
-        Vec::new()
-    };
+    folders.sort_by(|a, b| natord::compare_ignore_case(&a.name, &b.name));
 
-    let has_more = end_index < total_count;
+    Ok(folders)
+}
 
-    Ok(PaginatedFolderResult {
-        entries,
-        total_count,
-        has_more,
-        offset,
-        limit,
-    })
+// Helper function to keep entries whose name contains the query (case-insensitive); order is preserved
+fn filter_by_name(entries: Vec<FileEntry>, query: &str) -> Vec<FileEntry> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return entries;
+    }
+    entries.into_iter()
+        .filter(|entry| entry.name.to_lowercase().contains(&query))
+        .collect()
 }
 
-#[tauri::command]
-async fn get_folder_image_count(path: String) -> Result<usize, String> {
-    let target_path = PathBuf::from(path);
+// Helper function to keep entries rated at least `min_rating` stars; order is preserved
+fn filter_by_min_rating(entries: Vec<FileEntry>, min_rating: u8, cache: &MetadataCache) -> Result<Vec<FileEntry>, String> {
+    if min_rating == 0 {
+        return Ok(entries);
+    }
 
-    if !target_path.exists() {
-        return Err(format!("Path does not exist: {}", target_path.display()));
+    let mut filtered = Vec::new();
+    for entry in entries {
+        if cache.get_rating(&entry.path)? >= min_rating {
+            filtered.push(entry);
+        }
     }
+    Ok(filtered)
+}
 
-    if !target_path.is_dir() {
-        return Err(format!("Path is not a directory: {}", target_path.display()));
+// Helper function to keep entries that haven't been marked as seen; order is preserved
+fn filter_unseen(entries: Vec<FileEntry>, cache: &MetadataCache) -> Result<Vec<FileEntry>, String> {
+    let mut filtered = Vec::new();
+    for entry in entries {
+        if !cache.is_seen(&entry.path)? {
+            filtered.push(entry);
+        }
     }
+    Ok(filtered)
+}
 
-    let entries = collect_image_files(&target_path)?;
-    Ok(entries.len())
+// Filters shared by the folder listing commands; the default keeps everything but hidden files
+#[derive(Debug, Default)]
+struct ImageFilter {
+    query: Option<String>,
+    min_rating: Option<u8>,
+    unseen_only: bool,
+    show_hidden: bool,
 }
 
-#[tauri::command]
-async fn read_image_file(path: String, state: State<'_, AppState>) -> Result<ImageData, String> {
-    let image_path = Path::new(&path);
+// Helper function to filter then sort entries that arrive in name order
+fn filter_and_sort(entries: Vec<FileEntry>, sort: SortMode, filter: &ImageFilter, cache: &MetadataCache) -> Result<Vec<FileEntry>, String> {
+    let mut entries = entries;
 
-    if !image_path.exists() {
-        return Err(format!("Image file does not exist: {}", path));
+    if !filter.show_hidden {
+        entries.retain(|entry| !is_hidden(Path::new(&entry.path)));
     }
 
-    if !image_path.is_file() {
-        return Err(format!("Path is not a file: {}", path));
+    if let Some(query) = &filter.query {
+        entries = filter_by_name(entries, query);
     }
 
-    // Validate file extension
-    let supported_extensions = get_supported_image_extensions();
-    let extension = image_path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext.to_lowercase())
-        .ok_or_else(|| "File has no extension".to_string())?;
-
-    if !supported_extensions.contains(&extension) {
-        return Err(format!("Unsupported image format: {}", extension));
+    if let Some(min_rating) = filter.min_rating {
+        entries = filter_by_min_rating(entries, min_rating, cache)?;
     }
 
-    // Get file metadata
-    let metadata = fs::metadata(&image_path)
-        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
-
-    let file_size = metadata.len();
-    let last_modified = metadata.modified()
-        .map_err(|e| format!("Failed to get file modification time: {}", e))
-        .and_then(|time| {
-            Ok(DateTime::<Utc>::from(time).format("%Y-%m-%d %H:%M:%S UTC").to_string())
-        })?;
+    if filter.unseen_only {
+        entries = filter_unseen(entries, cache)?;
+    }
 
-    // Check cache first
-    let dimensions = if let Some(cached) = state.metadata_cache.get(&path, &last_modified)? {
-        // Cache hit! Use cached dimensions
-        ImageDimensions {
-            width: cached.width,
-            height: cached.height,
-        }
-    } else {
-        // Cache miss - read image dimensions from file
-        let dims = match ImageReader::open(&image_path) {
-            Ok(reader) => {
-                match reader.with_guessed_format() {
-                    Ok(reader_with_format) => {
-                        match reader_with_format.into_dimensions() {
-                            Ok((width, height)) => ImageDimensions { width, height },
-                            Err(e) => return Err(format!("Failed to read image dimensions: {}", e)),
-                        }
-                    }
-                    Err(e) => return Err(format!("Failed to detect image format: {}", e)),
-                }
-            }
-            Err(e) => return Err(format!("Failed to open image file: {}", e)),
-        };
+    Ok(apply_sort(entries, sort, cache))
+}
 
-        // Store in cache for future use
-        state.metadata_cache.set(&path, &last_modified, dims.width, dims.height, file_size)?;
+// List the images directly inside `dir`, filtered and sorted. This is the whole scan behind the
+// browse commands; it takes an explicit directory and no app state, so it can run against any folder.
+fn list_images(dir: &Path, sort: SortMode, filter: &ImageFilter, cache: &MetadataCache) -> Result<Vec<FileEntry>, String> {
+    filter_and_sort(collect_image_files(dir)?, sort, filter, cache)
+}
 
-        dims
+// Helper function to resolve the folder a browse command targets; no path means the working directory
+fn resolve_browse_path(path: Option<String>) -> Result<PathBuf, String> {
+    let target_path = match path {
+        Some(p) => PathBuf::from(p),
+        None => std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?,
     };
 
-    // Generate unique ID and asset URL
-    let id = Uuid::new_v4().to_string();
-    let name = image_path.file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("Unknown")
-        .to_string();
+    if !long_path(&target_path).exists() {
+        return Err(format!("Path does not exist: {}", target_path.display()));
+    }
 
-    // Create asset URL for Tauri's asset protocol
-    let asset_url = format!("asset://localhost/{}", path.replace("\\", "/"));
+    if !long_path(&target_path).is_dir() {
+        return Err(format!("Path is not a directory: {}", target_path.display()));
+    }
 
-    Ok(ImageData {
-        id,
-        name,
-        path,
-        asset_url,
-        dimensions,
-        file_size,
-        last_modified,
-    })
+    Ok(target_path)
 }
 
-// Batch version of read_image_file for efficient bulk loading
-#[tauri::command]
-async fn read_image_files_batch(paths: Vec<String>, state: State<'_, AppState>) -> Result<Vec<Option<ImageData>>, String> {
-    use tokio::task;
-
-    // Process images in parallel using tokio tasks
-    let mut handles = vec![];
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AspectFilter {
+    Landscape,
+    Portrait,
+    Square,
+}
 
-    for path in paths {
-        let cache = state.metadata_cache.clone();
-        let handle = task::spawn(async move {
-            read_image_file_internal(&path, &cache).await
-        });
-        handles.push(handle);
+impl AspectFilter {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "landscape" => Ok(Self::Landscape),
+            "portrait" => Ok(Self::Portrait),
+            "square" => Ok(Self::Square),
+            other => Err(format!("Unknown aspect filter: {}", other)),
+        }
     }
 
-    // Wait for all tasks to complete
-    let mut results = vec![];
-    for handle in handles {
-        match handle.await {
-            Ok(result) => results.push(result.ok()),
-            Err(_) => results.push(None),
+    fn matches(self, width: u32, height: u32) -> bool {
+        match self {
+            Self::Landscape => width > height,
+            Self::Portrait => height > width,
+            Self::Square => width == height,
         }
     }
+}
 
-    Ok(results)
+#[derive(Debug, Clone, Serialize)]
+pub struct AspectFilterWarning {
+    path: String,
+    unreadable: Vec<String>,
 }
 
-// Internal version of read_image_file that can be called from batch
-async fn read_image_file_internal(path: &str, cache: &Arc<MetadataCache>) -> Result<ImageData, String> {
-    let image_path = Path::new(path);
+// Helper function to keep only entries matching an aspect ratio.
+// Dimensions come from the metadata cache (decoding misses concurrently, which also warms the cache).
+// Images whose dimensions can't be read are dropped and reported in an `aspect-filter-warning` event.
+async fn filter_by_aspect(
+    app_handle: &tauri::AppHandle,
+    folder: &Path,
+    entries: Vec<FileEntry>,
+    aspect: AspectFilter,
+    cache: &Arc<MetadataCache>,
+    decode_permits: &Arc<Semaphore>,
+) -> Vec<FileEntry> {
+    let handles: Vec<_> = entries.iter()
+        .map(|entry| {
+            let path = entry.path.clone();
+            let cache = cache.clone();
+            let decode_permits = decode_permits.clone();
+            // Only the dimensions are used here, so the large-image flag doesn't matter
+            tokio::task::spawn(async move {
+                read_image_file_internal(&path, &cache, &decode_permits, u64::MAX, true).await
+            })
+        })
+        .collect();
 
-    if !image_path.exists() {
-        return Err(format!("Image file does not exist: {}", path));
+    let mut matching = Vec::new();
+    let mut unreadable = Vec::new();
+    for (entry, handle) in entries.into_iter().zip(handles) {
+        match handle.await {
+            Ok(Ok(image_data)) => {
+                if aspect.matches(image_data.dimensions.width, image_data.dimensions.height) {
+                    matching.push(entry);
+                }
+            }
+            _ => unreadable.push(entry.path),
+        }
     }
 
-    if !image_path.is_file() {
-        return Err(format!("Path is not a file: {}", path));
+    if !unreadable.is_empty() {
+        let _ = app_handle.emit("aspect-filter-warning", AspectFilterWarning {
+            path: folder.to_string_lossy().to_string(),
+            unreadable,
+        });
     }
 
-    // Validate file extension
-    let supported_extensions = get_supported_image_extensions();
-    let extension = image_path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext.to_lowercase())
-        .ok_or_else(|| "File has no extension".to_string())?;
+    matching
+}
 
-    if !supported_extensions.contains(&extension) {
-        return Err(format!("Unsupported image format: {}", extension));
+// Helper function to persist the last browsed folder (only written when it changes)
+fn remember_last_folder(folder: &Path, state: &AppState) {
+    let folder = folder.to_string_lossy().to_string();
+    if state.settings.lock().unwrap().last_folder.as_deref() == Some(folder.as_str()) {
+        return;
     }
+    if let Err(e) = update_settings(&state.settings, |settings| settings.last_folder = Some(folder)) {
+        eprintln!("Warning: Failed to save last folder: {}", e);
+    }
+}
 
-    // Get file metadata
-    let metadata = fs::metadata(&image_path)
-        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+#[tauri::command]
+async fn get_last_folder(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state.settings.lock().unwrap().last_folder.clone())
+}
 
-    let file_size = metadata.len();
-    let last_modified = metadata.modified()
-        .map_err(|e| format!("Failed to get file modification time: {}", e))
-        .and_then(|time| {
-            Ok(DateTime::<Utc>::from(time).format("%Y-%m-%d %H:%M:%S UTC").to_string())
-        })?;
+#[tauri::command]
+async fn set_large_image_threshold(pixels: u64, state: State<'_, AppState>) -> Result<(), String> {
+    if pixels == 0 {
+        return Err("Large image threshold must be greater than zero".to_string());
+    }
+    update_settings(&state.settings, |settings| settings.large_image_pixel_threshold = pixels)
+}
 
-    // Check cache first
-    let dimensions = if let Some(cached) = cache.get(path, &last_modified)? {
-        // Cache hit! Use cached dimensions
-        ImageDimensions {
-            width: cached.width,
-            height: cached.height,
-        }
-    } else {
-        // Cache miss - read image dimensions from file
-        let dims = match ImageReader::open(&image_path) {
-            Ok(reader) => {
-                match reader.with_guessed_format() {
-                    Ok(reader_with_format) => {
-                        match reader_with_format.into_dimensions() {
-                            Ok((width, height)) => ImageDimensions { width, height },
-                            Err(e) => return Err(format!("Failed to read image dimensions: {}", e)),
-                        }
-                    }
-                    Err(e) => return Err(format!("Failed to detect image format: {}", e)),
-                }
-            }
-            Err(e) => return Err(format!("Failed to open image file: {}", e)),
-        };
+#[tauri::command]
+async fn set_cache_db_path(path: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    // Takes effect on the next launch; the open connection keeps using the current file
+    update_settings(&state.settings, |settings| settings.cache_db_path = path)
+}
 
-        // Store in cache for future use
-        cache.set(path, &last_modified, dims.width, dims.height, file_size)?;
+#[tauri::command]
+async fn set_max_concurrent_decodes(limit: usize, state: State<'_, AppState>) -> Result<(), String> {
+    // Takes effect on the next launch; 0 means one per CPU
+    update_settings(&state.settings, |settings| settings.max_concurrent_decodes = limit)
+}
 
-        dims
+#[tauri::command]
+async fn set_oversize_limit(limit: u32, state: State<'_, AppState>) -> Result<(), String> {
+    update_settings(&state.settings, |settings| settings.oversize_limit = limit)
+}
+
+#[tauri::command]
+async fn set_exit_timeout(seconds: u64, state: State<'_, AppState>) -> Result<(), String> {
+    // 0 disables the watchdog and waits for the frontend indefinitely
+    update_settings(&state.settings, |settings| settings.exit_timeout_secs = seconds)
+}
+
+#[tauri::command]
+async fn set_preview_dir(path: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    // Previews already generated stay in the old directory; clear them first if they're no longer wanted
+    update_settings(&state.settings, |settings| settings.preview_dir = path)
+}
+
+#[tauri::command]
+async fn set_preview_format(format: String, jpeg_quality: Option<u8>, state: State<'_, AppState>) -> Result<(), String> {
+    let format = preview::PreviewFormat::parse(&format)?;
+    if let Some(quality) = jpeg_quality {
+        if !(1..=100).contains(&quality) {
+            return Err(format!("JPEG quality must be between 1 and 100, got {}", quality));
+        }
+    }
+
+    update_settings(&state.settings, |settings| {
+        settings.preview_format = format;
+        if let Some(quality) = jpeg_quality {
+            settings.preview_jpeg_quality = quality;
+        }
+    })
+}
+
+// Collapsed state of the frontend's session menu sections. Native (Tauri) submenus can't be collapsed
+// on any platform, so build_recent_sessions_submenu ignores this; the value is stored for the frontend only.
+#[tauri::command]
+async fn get_menu_sections_collapsed(state: State<'_, AppState>) -> Result<HashMap<String, bool>, String> {
+    Ok(state.settings.lock().unwrap().menu_sections_collapsed.clone())
+}
+
+#[tauri::command]
+async fn set_menu_section_collapsed(section: String, collapsed: bool, state: State<'_, AppState>) -> Result<(), String> {
+    update_settings(&state.settings, |settings| {
+        // Expanded is the default, so only collapsed sections need to be stored
+        if collapsed {
+            settings.menu_sections_collapsed.insert(section, true);
+        } else {
+            settings.menu_sections_collapsed.remove(&section);
+        }
+    })
+}
+
+#[tauri::command]
+async fn set_warm_cache_on_startup(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    update_settings(&state.settings, |settings| settings.warm_cache_on_startup = enabled)
+}
+
+#[tauri::command]
+async fn set_auto_mark_seen(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    update_settings(&state.settings, |settings| settings.auto_mark_seen = enabled)
+}
+
+#[tauri::command]
+async fn set_reopen_last_folder(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    update_settings(&state.settings, |settings| settings.reopen_last_folder = enabled)
+}
+
+// Upper bound for a single page so the frontend can't accidentally request everything at once
+const MAX_PAGE_SIZE: usize = 5000;
+
+// Each filter is a separate optional argument so the frontend can pass only what it needs
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn browse_folder_paginated(
+    path: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    include_total_bytes: Option<bool>,
+    query: Option<String>,
+    min_rating: Option<u8>,
+    unseen_only: Option<bool>,
+    sort: Option<String>,
+    show_hidden: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<PaginatedFolderResult, String> {
+    let sort = sort.as_deref().map(SortMode::parse).transpose()?.unwrap_or(SortMode::Name);
+    let target_path = resolve_browse_path(path)?;
+    // Hidden files are left out by default, as in browse_folder, so pages and counts agree with it
+    let filter = ImageFilter {
+        query,
+        min_rating,
+        unseen_only: unseen_only.unwrap_or(false),
+        show_hidden: show_hidden.unwrap_or(false),
+    };
+
+    state.allowed_paths.allow(&target_path);
+
+    // Collect all image files, filtering by name, rating and seen state before paginating so total_count matches the filter.
+    // The signature is taken from the unfiltered listing.
+    let all_entries = collect_image_files(&target_path)?;
+    let signature = quick_folder_signature(&target_path, all_entries.len());
+    // Sorted before slicing so pages follow the requested order across the whole folder
+    let mut all_entries = filter_and_sort(all_entries, sort, &filter, &state.metadata_cache)?;
+    let total_count = all_entries.len();
+
+    // Stat every file to fill in sizes and the folder total (opt-in, since it's slow for huge folders)
+    let total_bytes = if include_total_bytes.unwrap_or(false) {
+        let mut total = 0;
+        for entry in all_entries.iter_mut() {
+            if let Ok(metadata) = fs::metadata(long_path(Path::new(&entry.path))) {
+                entry.size = Some(metadata.len());
+                total += metadata.len();
+            }
+        }
+        Some(total)
+    } else {
+        None
+    };
+
+    // Apply pagination
+    let offset = offset.unwrap_or(0);
+    let limit = limit
+        .unwrap_or_else(|| state.settings.lock().unwrap().default_page_size)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let end_index = std::cmp::min(offset + limit, total_count);
+    let entries: Vec<FileEntry> = if offset < total_count {
+        all_entries[offset..end_index].to_vec()
+    } else {
+        Vec::new()
+    };
+
+    let has_more = end_index < total_count;
+
+    Ok(PaginatedFolderResult {
+        entries,
+        total_count,
+        has_more,
+        offset,
+        limit,
+        total_bytes,
+        signature,
+    })
+}
+
+// Number of entries sent per folder-entry-batch event when streaming a folder listing
+const FOLDER_STREAM_BATCH_SIZE: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderEntryBatch {
+    path: String,
+    entries: Vec<FileEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderScanComplete {
+    path: String,
+    total_count: usize,
+}
+
+// Streaming variant of browse_folder for very large folders.
+// Entries are emitted as `folder-entry-batch` events in discovery order (NOT sorted),
+// followed by a `folder-scan-complete` event; the frontend sorts once the scan completes.
+// Returns the total number of images found.
+#[tauri::command]
+async fn browse_folder_streaming(
+    app_handle: tauri::AppHandle,
+    path: String,
+    operation_id: Option<String>,
+    show_hidden: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let operation = state.operations.start(operation_id);
+    let show_hidden = show_hidden.unwrap_or(false);
+
+    let target_path = PathBuf::from(&path);
+
+    if !long_path(&target_path).exists() {
+        return Err(format!("Path does not exist: {}", target_path.display()));
+    }
+
+    if !long_path(&target_path).is_dir() {
+        return Err(format!("Path is not a directory: {}", target_path.display()));
+    }
+
+    state.allowed_paths.allow(&target_path);
+
+    let supported_extensions = get_supported_image_extensions();
+    let dir_entries = fs::read_dir(long_path(&target_path))
+        .map_err(|e| format!("Failed to read directory: {}", describe_io_error(&e, &target_path)))?;
+
+    let mut batch = Vec::with_capacity(FOLDER_STREAM_BATCH_SIZE);
+    let mut total_count = 0;
+
+    for dir_entry in dir_entries.flatten() {
+        operation.check()?;
+
+        if !show_hidden && is_hidden(&dir_entry.path()) {
+            continue;
+        }
+        if let Some(entry) = image_entry_from_dir_entry(&dir_entry, &supported_extensions) {
+            batch.push(entry);
+            total_count += 1;
+        }
+
+        if batch.len() >= FOLDER_STREAM_BATCH_SIZE {
+            let _ = app_handle.emit("folder-entry-batch", FolderEntryBatch {
+                path: path.clone(),
+                entries: std::mem::take(&mut batch),
+            });
+        }
+    }
+
+    if !batch.is_empty() {
+        let _ = app_handle.emit("folder-entry-batch", FolderEntryBatch {
+            path: path.clone(),
+            entries: batch,
+        });
+    }
+
+    let _ = app_handle.emit("folder-scan-complete", FolderScanComplete {
+        path,
+        total_count,
+    });
+
+    Ok(total_count)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderImageCount {
+    count: usize,
+    partial: bool, // True when some entries couldn't be read and were left out of the count
+}
+
+#[tauri::command]
+async fn set_default_page_size(page_size: usize, state: State<'_, AppState>) -> Result<(), String> {
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(format!("Page size must be between 1 and {}", MAX_PAGE_SIZE));
+    }
+    update_settings(&state.settings, |settings| settings.default_page_size = page_size)
+}
+
+#[tauri::command]
+async fn get_folder_image_count(path: String, show_hidden: Option<bool>) -> Result<FolderImageCount, String> {
+    let target_path = PathBuf::from(path);
+    let show_hidden = show_hidden.unwrap_or(false);
+
+    if !long_path(&target_path).exists() {
+        return Err(format!("Path does not exist: {}", target_path.display()));
+    }
+
+    if !long_path(&target_path).is_dir() {
+        return Err(format!("Path is not a directory: {}", target_path.display()));
+    }
+
+    let supported_extensions = get_supported_image_extensions();
+    let dir_entries = fs::read_dir(long_path(&target_path))
+        .map_err(|e| format!("Failed to read directory: {}", describe_io_error(&e, &target_path)))?;
+
+    // Best-effort count: unreadable entries (e.g. restricted paths on network shares) are skipped
+    let mut count = 0;
+    let mut partial = false;
+    for dir_entry in dir_entries {
+        match dir_entry {
+            Ok(dir_entry) => {
+                if !show_hidden && is_hidden(&dir_entry.path()) {
+                    continue;
+                }
+                if image_entry_from_dir_entry(&dir_entry, &supported_extensions).is_some() {
+                    count += 1;
+                }
+            }
+            Err(e) => {
+                eprintln!("Skipping unreadable entry in {}: {}", target_path.display(), e);
+                partial = true;
+            }
+        }
+    }
+
+    Ok(FolderImageCount { count, partial })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderMegapixels {
+    total_pixels: u64,
+    average_pixels: f64, // Over the measured images only; 0 when none could be measured
+    measured_count: usize,
+    unreadable_count: usize, // Images that vanished or couldn't be measured; left out of the totals
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderMegapixelsProgress {
+    path: String,
+    processed: usize,
+    total: usize,
+}
+
+// How often get_folder_megapixels reports progress, in images
+const MEGAPIXELS_PROGRESS_INTERVAL: usize = 100;
+
+// Sum width x height over a folder's images. Cached dimensions are used where fresh; misses are
+// measured concurrently through the decode pool. Folders larger than the progress interval
+// report `folder-megapixels-progress` events as images are measured.
+#[tauri::command]
+async fn get_folder_megapixels(
+    app_handle: tauri::AppHandle,
+    path: String,
+    operation_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FolderMegapixels, String> {
+    use tokio::task;
+
+    let operation = state.operations.start(operation_id);
+    let target_path = PathBuf::from(&path);
+
+    if !target_path.is_dir() {
+        return Err(format!("Path is not a directory: {}", target_path.display()));
+    }
+
+    state.allowed_paths.allow(&target_path);
+    let entries = collect_image_files(&target_path)?;
+    let total = entries.len();
+
+    let mut handles = vec![];
+    for entry in entries {
+        let cache = state.metadata_cache.clone();
+        let decode_permits = state.decode_permits.clone();
+        handles.push(task::spawn(async move {
+            read_image_file_internal(&entry.path, &cache, &decode_permits, u64::MAX, true).await
+        }));
+    }
+
+    let mut total_pixels: u64 = 0;
+    let mut measured_count = 0;
+    let mut unreadable_count = 0;
+    for index in 0..handles.len() {
+        if let Err(e) = operation.check() {
+            // Abort the reads still queued for a decode permit; a decode already running finishes
+            for handle in &handles[index..] {
+                handle.abort();
+            }
+            return Err(e);
+        }
+
+        match (&mut handles[index]).await {
+            Ok(Ok(image_data)) => {
+                total_pixels += image_data.dimensions.width as u64 * image_data.dimensions.height as u64;
+                measured_count += 1;
+            }
+            _ => unreadable_count += 1,
+        }
+
+        let processed = index + 1;
+        if total > MEGAPIXELS_PROGRESS_INTERVAL && (processed % MEGAPIXELS_PROGRESS_INTERVAL == 0 || processed == total) {
+            let _ = app_handle.emit("folder-megapixels-progress", FolderMegapixelsProgress {
+                path: path.clone(),
+                processed,
+                total,
+            });
+        }
+    }
+
+    let average_pixels = if measured_count > 0 {
+        total_pixels as f64 / measured_count as f64
+    } else {
+        0.0
     };
+    println!("Measured {} images in {} ({} unreadable)", measured_count, path, unreadable_count);
+
+    Ok(FolderMegapixels {
+        total_pixels,
+        average_pixels,
+        measured_count,
+        unreadable_count,
+    })
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FormatStats {
+    count: usize,
+    total_bytes: u64, // Files that vanished mid-scan count with 0 bytes
+}
+
+// Count a folder's images and their bytes per type, keyed by lowercase extension. With `by_content`
+// files are keyed by their sniffed format instead (e.g. a misnamed PNG counts as "png"), which opens
+// every file, and only formats the `image` crate recognises are counted.
+#[tauri::command]
+async fn get_format_breakdown(path: String, by_content: Option<bool>) -> Result<HashMap<String, FormatStats>, String> {
+    let target_path = PathBuf::from(&path);
+
+    if !target_path.is_dir() {
+        return Err(format!("Path is not a directory: {}", target_path.display()));
+    }
+
+    let by_content = by_content.unwrap_or(false);
+    let entries = if by_content {
+        collect_image_files_by_content(&target_path)?
+    } else {
+        collect_image_files(&target_path)?
+    };
+
+    let mut breakdown: HashMap<String, FormatStats> = HashMap::new();
+    for entry in entries {
+        let entry_path = Path::new(&entry.path);
+        let format = if by_content {
+            sniff_image_format(entry_path).and_then(|format| format.extensions_str().first().map(|ext| ext.to_string()))
+        } else {
+            entry_path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase())
+        };
+        let Some(format) = format else {
+            continue;
+        };
+
+        let stats = breakdown.entry(format).or_default();
+        stats.count += 1;
+        stats.total_bytes += fs::metadata(long_path(entry_path)).map(|metadata| metadata.len()).unwrap_or(0);
+    }
+
+    Ok(breakdown)
+}
+
+#[tauri::command]
+async fn get_largest_images(path: String, n: usize) -> Result<Vec<FileEntry>, String> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let target_path = PathBuf::from(&path);
+
+    if !long_path(&target_path).is_dir() {
+        return Err(format!("Path is not a directory: {}", target_path.display()));
+    }
+
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    // Min-heap of the n largest files seen so far, so the whole folder never needs sorting
+    let mut largest: BinaryHeap<Reverse<(u64, usize)>> = BinaryHeap::with_capacity(n + 1);
+    let mut entries = collect_image_files(&target_path)?;
+
+    for (index, entry) in entries.iter_mut().enumerate() {
+        let Ok(metadata) = fs::metadata(long_path(Path::new(&entry.path))) else {
+            continue;
+        };
+        entry.size = Some(metadata.len());
+
+        largest.push(Reverse((metadata.len(), index)));
+        if largest.len() > n {
+            largest.pop();
+        }
+    }
+
+    // into_sorted_vec on Reverse yields largest first
+    Ok(largest.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((_, index))| entries[index].clone())
+        .collect())
+}
+
+#[tauri::command]
+async fn read_image_file(
+    path: String,
+    stable_id: Option<bool>,
+    include_data_url: Option<bool>,
+    use_cache: Option<bool>,
+    preview_frame: Option<u32>, // Frame of an animated GIF/WebP the preview is made from; the first by default
+    state: State<'_, AppState>,
+) -> Result<ImageData, String> {
+    is_path_allowed(&state, &path)?;
+
+    let (large_image_threshold, oversize_limit) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.large_image_pixel_threshold, settings.oversize_limit)
+    };
+    let use_cache = use_cache.unwrap_or(true);
+    let mut image_data = read_image_file_internal(&path, &state.metadata_cache, &state.decode_permits, large_image_threshold, use_cache).await?;
+
+    // Ids are random per read unless the caller wants identity that survives reloads
+    if stable_id.unwrap_or(false) {
+        image_data.id = stable_image_id(&path);
+    }
+
+    // Raw files can't be displayed directly, so their embedded JPEG stands in for both URLs
+    let is_raw = is_raw_file(Path::new(&path));
+    #[cfg(feature = "raw")]
+    if is_raw {
+        let source = PathBuf::from(&path);
+        let last_modified = image_data.last_modified.clone();
+        let file_size = image_data.file_size;
+        let config = preview::PreviewConfig::from_settings(&state.settings.lock().unwrap())?;
+        let preview_path = tokio::task::spawn_blocking(move || preview::ensure_embedded_preview(&source, &last_modified, file_size, &config, raw::extract_preview))
+            .await
+            .map_err(|e| format!("Preview task failed: {}", e))??;
+
+        // Previews live outside the browsed folder, so their directory has to be served too
+        state.allowed_paths.allow(&preview_path);
+        image_data.preview_url = Some(asset_url_for(&preview_path.to_string_lossy()));
+        let preview_size = fs::metadata(&preview_path).map(|metadata| metadata.len()).unwrap_or(u64::MAX);
+        if include_data_url.unwrap_or(false) && preview_size <= DATA_URL_MAX_BYTES {
+            image_data.data_url = Some(encode_data_url(&preview_path)?);
+        }
+    }
+
+    // Inline small files for setups where asset:// URLs can't be loaded
+    if !is_raw && include_data_url.unwrap_or(false) && image_data.file_size <= DATA_URL_MAX_BYTES {
+        image_data.data_url = Some(encode_data_url(Path::new(&path))?);
+    }
+
+    // Oversized images also get a cached, capped-resolution preview for responsive display
+    let ImageDimensions { width, height } = image_data.dimensions;
+    if !is_raw && oversize_limit > 0 && (width > oversize_limit || height > oversize_limit) {
+        let source = PathBuf::from(&path);
+        let last_modified = image_data.last_modified.clone();
+        let file_size = image_data.file_size;
+        let config = preview::PreviewConfig::from_settings(&state.settings.lock().unwrap())?;
+        let _permit = acquire_decode_permit(&state.decode_permits).await?;
+        let preview = tokio::task::spawn_blocking(move || preview::ensure_preview(&source, &last_modified, file_size, oversize_limit, preview_frame.unwrap_or(0), &config))
+            .await
+            .map_err(|e| format!("Preview task failed: {}", e))
+            .and_then(|result| result);
+        match preview {
+            Ok(preview_path) => {
+                state.allowed_paths.allow(&preview_path);
+                image_data.preview_url = Some(asset_url_for(&preview_path.to_string_lossy()));
+            }
+            Err(e) => eprintln!("Warning: Failed to generate preview for {}: {}", path, e),
+        }
+    }
+
+    // Only the single-image read counts as viewing; batch and filter reads don't mark anything,
+    // and neither do uncached (benchmark and diagnostic) reads
+    if use_cache {
+        if let Err(e) = state.metadata_cache.mark_viewed(&path) {
+            eprintln!("Warning: Failed to record view of {}: {}", path, e);
+        }
+    }
+    if use_cache && state.settings.lock().unwrap().auto_mark_seen {
+        if let Err(e) = state.metadata_cache.mark_seen(&path) {
+            eprintln!("Warning: Failed to mark {} as seen: {}", path, e);
+        }
+    }
+
+    Ok(image_data)
+}
+
+// Re-read an image's info from disk, replacing whatever the metadata cache held for it
+#[tauri::command]
+async fn refresh_image_file(path: String, state: State<'_, AppState>) -> Result<ImageData, String> {
+    is_path_allowed(&state, &path)?;
+
+    // The cached row is keyed by mtime, so an in-place edit that kept the mtime would otherwise be served stale
+    state.metadata_cache.remove(&path)?;
+    println!("Refreshing image info for {}", path);
+
+    read_image_file(path, None, None, None, None, state).await
+}
+
+// Batch version of read_image_file for efficient bulk loading
+#[tauri::command]
+async fn read_image_files_batch(paths: Vec<String>, stable_id: Option<bool>, state: State<'_, AppState>) -> Result<Vec<Option<ImageData>>, String> {
+    use tokio::task;
+
+    let large_image_threshold = state.settings.lock().unwrap().large_image_pixel_threshold;
+
+    // Process images in parallel using tokio tasks
+    let mut handles = vec![];
+
+    for path in paths {
+        let allowed = is_path_allowed(&state, &path);
+        let cache = state.metadata_cache.clone();
+        let decode_permits = state.decode_permits.clone();
+        let handle = task::spawn(async move {
+            allowed?;
+            let mut image_data = read_image_file_internal(&path, &cache, &decode_permits, large_image_threshold, true).await?;
+            if stable_id.unwrap_or(false) {
+                image_data.id = stable_image_id(&path);
+            }
+            Ok::<_, String>(image_data)
+        });
+        handles.push(handle);
+    }
+
+    // Wait for all tasks to complete
+    let mut results = vec![];
+    for handle in handles {
+        match handle.await {
+            Ok(result) => results.push(result.ok()),
+            Err(_) => results.push(None),
+        }
+    }
+
+    Ok(results)
+}
+
+pub type ResolvedTabImage = (String, Result<ImageData, String>);
+
+// Read the image behind every tab of a session at once, in tab order; a missing or unreadable
+// file only fails its own tab
+#[tauri::command]
+async fn resolve_session_images(session_data: SessionData, state: State<'_, AppState>) -> Result<Vec<ResolvedTabImage>, String> {
+    Ok(resolve_tab_images(&session_data, &state).await)
+}
+
+// Helper function behind resolve_session_images
+async fn resolve_tab_images(session_data: &SessionData, state: &AppState) -> Vec<ResolvedTabImage> {
+    use tokio::task;
+
+    let large_image_threshold = state.settings.lock().unwrap().large_image_pixel_threshold;
+
+    let mut tabs: Vec<&SessionTab> = session_data.tabs.iter().collect();
+    tabs.sort_by_key(|tab| tab.order);
+
+    let mut handles = vec![];
+    for tab in tabs {
+        let path = tab.image_path.clone();
+        let allowed = is_path_allowed(state, &path);
+        let cache = state.metadata_cache.clone();
+        let decode_permits = state.decode_permits.clone();
+        let handle = task::spawn(async move {
+            allowed?;
+            read_image_file_internal(&path, &cache, &decode_permits, large_image_threshold, true).await
+        });
+        handles.push((tab.id.clone(), handle));
+    }
+
+    let mut results = vec![];
+    for (tab_id, handle) in handles {
+        let result = handle.await
+            .map_err(|e| format!("Image task failed: {}", e))
+            .and_then(|result| result);
+        results.push((tab_id, result));
+    }
+
+    results
+}
+
+// Helper function to collect the tabs of a session whose images can't be read, in tab order
+async fn session_image_errors(session_data: &SessionData, state: &AppState) -> Vec<SessionImageError> {
+    let paths: HashMap<&str, &str> = session_data.tabs.iter()
+        .map(|tab| (tab.id.as_str(), tab.image_path.as_str()))
+        .collect();
+
+    resolve_tab_images(session_data, state).await
+        .into_iter()
+        .filter_map(|(tab_id, result)| {
+            let error = result.err()?;
+            let path = paths.get(tab_id.as_str()).copied().unwrap_or_default().to_string();
+            Some(SessionImageError { tab_id, path, error })
+        })
+        .collect()
+}
+
+// Helper function to wait for a decode slot (see AppState::decode_permits); hold the permit while decoding
+async fn acquire_decode_permit(permits: &Semaphore) -> Result<SemaphorePermit<'_>, String> {
+    permits.acquire().await.map_err(|e| format!("Decode pool unavailable: {}", e))
+}
+
+// Largest file inlined as a data URL; base64 adds a third on top of this
+const DATA_URL_MAX_BYTES: u64 = 2 * 1024 * 1024;
+
+// Helper function to read a file into a base64 `data:` URL
+fn encode_data_url(path: &Path) -> Result<String, String> {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+    let mime = path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(mime_type_for_extension)
+        .unwrap_or("application/octet-stream");
+    let bytes = fs::read(long_path(path))
+        .map_err(|e| format!("Failed to read image file: {}", describe_io_error(&e, path)))?;
+
+    Ok(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
+
+// Namespace for stable image ids; never change it, or every stored id stops matching
+const IMAGE_ID_NAMESPACE: Uuid = Uuid::from_u128(0x8cb178a3_2ebe_4335_9f40_4b38f49c3597);
+
+// Helper function to derive an id that stays the same every time a file is read
+fn stable_image_id(path: &str) -> String {
+    let canonical_path = canonicalize_path(Path::new(path));
+    Uuid::new_v5(&IMAGE_ID_NAMESPACE, canonical_path.to_string_lossy().as_bytes()).to_string()
+}
+
+// Helper function to create an asset URL for Tauri's asset protocol
+fn asset_url_for(path: &str) -> String {
+    format!("asset://localhost/{}", path.replace("\\", "/"))
+}
+
+// Helper function to format a file's modification time the way cache entries are stamped
+fn format_last_modified(metadata: &fs::Metadata) -> Result<String, String> {
+    metadata.modified()
+        .map_err(|e| format!("Failed to get file modification time: {}", e))
+        .map(|time| DateTime::<Utc>::from(time).format("%Y-%m-%d %H:%M:%S UTC").to_string())
+}
+
+// Internal version of read_image_file that can be called from batch
+async fn read_image_file_internal(
+    path: &str,
+    cache: &Arc<MetadataCache>,
+    decode_permits: &Semaphore,
+    large_image_threshold: u64,
+    use_cache: bool, // False leaves the database untouched (no lookup, store or rating), always measuring the file afresh
+) -> Result<ImageData, String> {
+    let image_path = Path::new(path);
+    let fs_path = long_path(image_path);
+
+    if !fs_path.exists() {
+        return Err(format!("Image file does not exist: {}", path));
+    }
+
+    if !fs_path.is_file() {
+        return Err(format!("Path is not a file: {}", path));
+    }
+
+    // Validate file extension. Errors lead with a kind (NoExtension, UnsupportedExtension,
+    // FormatMismatch) so the UI can tell a misnamed file from one it can't show at all.
+    let supported_extensions = get_supported_image_extensions();
+    // Extensionless files are still readable when their content identifies them (see browse_folder's verify_content)
+    let extension = image_path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .or_else(|| sniff_image_format(&fs_path).and_then(|format| format.extensions_str().first().map(|ext| ext.to_string())))
+        .ok_or_else(|| format!("NoExtension: {} has no file extension", path))?;
+
+    if !supported_extensions.contains(&extension) {
+        return Err(format!("UnsupportedExtension: .{} files are not supported", extension));
+    }
+
+    // Get file metadata
+    let metadata = fs::metadata(&fs_path)
+        .map_err(|e| format!("Failed to read file metadata: {}", describe_io_error(&e, image_path)))?;
+
+    let file_size = metadata.len();
+    let last_modified = format_last_modified(&metadata)?;
+
+    // Check cache first
+    let cache_hit = if use_cache { cache.get(path, &last_modified)? } else { None };
+    let cached = if let Some(cached) = cache_hit {
+        // Cache hit! Use cached dimensions
+        cached
+    } else {
+        // Cache miss - read image dimensions from file
+        let _permit = acquire_decode_permit(decode_permits).await?;
+        let ProbedImage { width, height, format: image_format } = dimension_readers()
+            .for_extension(&extension)
+            .read_dimensions(&fs_path)?;
+
+        let icc_profile = image_format.and_then(|format| color_profile::read_icc_profile(&fs_path, format));
+
+        let metadata = CachedMetadata {
+            width,
+            height,
+            file_size,
+            // Fall back to the extension when the content can't be identified
+            format: image_format
+                .map(|format| format!("{:?}", format).to_uppercase())
+                .unwrap_or_else(|| extension.to_uppercase()),
+            has_icc_profile: icc_profile.is_some(),
+            icc_profile_name: icc_profile.as_deref().and_then(color_profile::profile_description),
+        };
+
+        // Store in cache for future use
+        if use_cache {
+            cache.set(path, &last_modified, &metadata)?;
+        }
+
+        metadata
+    };
+
+    check_format_matches_extension(path, &extension, &cached.format)?;
+
+    let rating = if use_cache { cache.get_rating(path)? } else { 0 };
+    let pixel_count = cached.width as u64 * cached.height as u64;
 
     // Generate unique ID and asset URL
     let id = Uuid::new_v4().to_string();
@@ -453,32 +1553,95 @@ async fn read_image_file_internal(path: &str, cache: &Arc<MetadataCache>) -> Res
         .unwrap_or("Unknown")
         .to_string();
 
-    // Create asset URL for Tauri's asset protocol
-    let asset_url = format!("asset://localhost/{}", path.replace("\\", "/"));
+    let asset_url = asset_url_for(path);
 
     Ok(ImageData {
         id,
         name,
         path: path.to_string(),
         asset_url,
-        dimensions,
+        dimensions: ImageDimensions {
+            width: cached.width,
+            height: cached.height,
+        },
         file_size,
         last_modified,
+        format: cached.format,
+        has_icc_profile: cached.has_icc_profile,
+        icc_profile_name: cached.icc_profile_name,
+        rating,
+        pixel_count,
+        large_image: pixel_count > large_image_threshold,
+        preview_url: None,
+        data_url: None,
     })
 }
 
+// Single source of truth for supported image types: (extension, MIME type)
+const SUPPORTED_IMAGE_TYPES: &[(&str, &str)] = &[
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("png", "image/png"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("bmp", "image/bmp"),
+    ("tiff", "image/tiff"),
+    ("tif", "image/tiff"),
+    ("ico", "image/x-icon"),
+];
+
+// Helper function to reject files whose content is a different format than their extension claims,
+// e.g. a JPEG saved as `.png`. `detected` is the cached format name, which falls back to the
+// uppercased extension when the content couldn't be identified; that case isn't a mismatch.
+fn check_format_matches_extension(path: &str, extension: &str, detected: &str) -> Result<(), String> {
+    let Some(expected) = image::ImageFormat::from_extension(extension) else {
+        return Ok(());
+    };
+    let expected = format!("{:?}", expected).to_uppercase();
+
+    if detected == expected || detected == extension.to_uppercase() {
+        return Ok(());
+    }
+    Err(format!("FormatMismatch: {} has a .{} extension (expected {}) but contains {} data", path, extension, expected, detected))
+}
+
+// Helper function to look up the MIME type for a supported extension (case-insensitive)
+fn mime_type_for_extension(extension: &str) -> Option<&'static str> {
+    supported_image_types()
+        .find(|(supported, _)| supported.eq_ignore_ascii_case(extension))
+        .map(|(_, mime)| *mime)
+}
+
+// Helper function to list every supported image type, including camera raw types when built with the `raw` feature
+fn supported_image_types() -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+    #[cfg(feature = "raw")]
+    let raw_types = raw::RAW_IMAGE_TYPES;
+    #[cfg(not(feature = "raw"))]
+    let raw_types: &[(&str, &str)] = &[];
+
+    SUPPORTED_IMAGE_TYPES.iter().chain(raw_types)
+}
+
+// Helper function to tell whether a file is a camera raw file (never, without the `raw` feature)
+#[cfg_attr(not(feature = "raw"), allow(unused_variables))]
+fn is_raw_file(path: &Path) -> bool {
+    #[cfg(feature = "raw")]
+    if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
+        return raw::is_raw_extension(extension);
+    }
+    false
+}
+
 fn get_supported_image_extensions() -> Vec<String> {
-    vec![
-        "jpg".to_string(),
-        "jpeg".to_string(),
-        "png".to_string(),
-        "gif".to_string(),
-        "webp".to_string(),
-        "bmp".to_string(),
-        "tiff".to_string(),
-        "tif".to_string(),
-        "ico".to_string(),
-    ]
+    supported_image_types()
+        .map(|(extension, _)| extension.to_string())
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageMimeType {
+    extension: String,
+    mime: String,
 }
 
 #[tauri::command]
@@ -487,15 +1650,64 @@ async fn get_supported_image_types() -> Vec<String> {
 }
 
 #[tauri::command]
-async fn open_folder_dialog(app_handle: tauri::AppHandle) -> Result<Option<String>, String> {
+async fn get_supported_mime_types() -> Vec<ImageMimeType> {
+    supported_image_types()
+        .map(|(extension, mime)| ImageMimeType {
+            extension: extension.to_string(),
+            mime: mime.to_string(),
+        })
+        .collect()
+}
+
+// How long pick dialogs may stay unanswered before giving up on the platform callback
+const DIALOG_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+// Helper function to await a dialog callback, failing with a DialogTimeout error if it never fires
+async fn await_dialog_response<T>(rx: tokio::sync::oneshot::Receiver<Option<T>>) -> Result<Option<T>, String> {
+    match tokio::time::timeout(DIALOG_TIMEOUT, rx).await {
+        Ok(Ok(result)) => Ok(result), // None when the user cancelled the dialog
+        Ok(Err(_)) => Err("Dialog operation failed".to_string()),
+        Err(_) => Err(format!("DialogTimeout: no response from the dialog after {} seconds", DIALOG_TIMEOUT.as_secs())),
+    }
+}
+
+// Helper function to pick the directory a dialog should open in: the explicit hint, else the last one used
+fn resolve_dialog_start_dir(start_dir: Option<String>, state: &AppState) -> Option<PathBuf> {
+    start_dir
+        .or_else(|| state.settings.lock().unwrap().last_dialog_dir.clone())
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+}
+
+// Helper function to remember the directory a dialog was last used in
+fn remember_dialog_dir(dir: Option<&Path>, state: &AppState) {
+    if let Some(dir) = dir {
+        let dir = dir.to_string_lossy().to_string();
+        if let Err(e) = update_settings(&state.settings, |settings| settings.last_dialog_dir = Some(dir)) {
+            eprintln!("Warning: Failed to save last dialog directory: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+async fn open_folder_dialog(
+    app_handle: tauri::AppHandle,
+    start_dir: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
     use tauri_plugin_dialog::DialogExt;
     use std::sync::{Arc, Mutex};
     use tokio::sync::oneshot;
     
     let (tx, rx) = oneshot::channel();
     let tx = Arc::new(Mutex::new(Some(tx)));
-    
-    app_handle.dialog().file().pick_folder(move |folder_path| {
+
+    let mut dialog = app_handle.dialog().file();
+    if let Some(dir) = resolve_dialog_start_dir(start_dir, &state) {
+        dialog = dialog.set_directory(dir);
+    }
+
+    dialog.pick_folder(move |folder_path| {
         if let Ok(mut sender) = tx.lock() {
             if let Some(tx) = sender.take() {
                 let _ = tx.send(folder_path);
@@ -503,18 +1715,71 @@ async fn open_folder_dialog(app_handle: tauri::AppHandle) -> Result<Option<Strin
         }
     });
     
-    match rx.await {
-        Ok(Some(folder_path)) => {
-            let path_str = folder_path.to_string();
-            Ok(Some(path_str))
+    let folder_path = await_dialog_response(rx).await?.map(|folder_path| folder_path.to_string());
+    remember_dialog_dir(folder_path.as_deref().map(Path::new), &state);
+    if let Some(folder_path) = &folder_path {
+        state.allowed_paths.allow(Path::new(folder_path));
+    }
+    Ok(folder_path)
+}
+
+#[tauri::command]
+async fn open_image_dialog(
+    app_handle: tauri::AppHandle,
+    starting_path: Option<String>,
+    start_dir: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::DialogExt;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::oneshot;
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Arc::new(Mutex::new(Some(tx)));
+
+    let supported_extensions = get_supported_image_extensions();
+    let extensions: Vec<&str> = supported_extensions.iter().map(|s| s.as_str()).collect();
+
+    let mut dialog = app_handle.dialog().file()
+        .add_filter("Image Files", &extensions);
+
+    if let Some(dir) = resolve_dialog_start_dir(start_dir, &state) {
+        dialog = dialog.set_directory(dir);
+    }
+
+    // A starting file takes precedence and also preselects its name
+    if let Some(path_str) = starting_path {
+        let path = std::path::Path::new(&path_str);
+        if let Some(parent) = path.parent() {
+            dialog = dialog.set_directory(parent);
         }
-        Ok(None) => Ok(None), // User cancelled the dialog
-        Err(_) => Err("Dialog operation failed".to_string()),
+        if let Some(filename) = path.file_name() {
+            dialog = dialog.set_file_name(filename.to_string_lossy());
+        }
+    }
+
+    dialog.pick_file(move |file_path| {
+            if let Ok(mut sender) = tx.lock() {
+                if let Some(tx) = sender.take() {
+                    let _ = tx.send(file_path);
+                }
+            }
+        });
+    
+    let file_path = await_dialog_response(rx).await?.map(|file_path| file_path.to_string());
+    remember_dialog_dir(file_path.as_deref().and_then(|path| Path::new(path).parent()), &state);
+    if let Some(file_path) = &file_path {
+        state.allowed_paths.allow(Path::new(file_path));
     }
+    Ok(file_path)
 }
 
 #[tauri::command]
-async fn open_image_dialog(app_handle: tauri::AppHandle, starting_path: Option<String>) -> Result<Option<String>, String> {
+async fn open_images_dialog(
+    app_handle: tauri::AppHandle,
+    start_dir: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<Vec<String>>, String> {
     use tauri_plugin_dialog::DialogExt;
     use std::sync::{Arc, Mutex};
     use tokio::sync::oneshot;
@@ -522,42 +1787,153 @@ async fn open_image_dialog(app_handle: tauri::AppHandle, starting_path: Option<S
     let (tx, rx) = oneshot::channel();
     let tx = Arc::new(Mutex::new(Some(tx)));
 
-    let supported_extensions = get_supported_image_extensions();
-    let extensions: Vec<&str> = supported_extensions.iter().map(|s| s.as_str()).collect();
+    let supported_extensions = get_supported_image_extensions();
+    let extensions: Vec<&str> = supported_extensions.iter().map(|s| s.as_str()).collect();
+
+    let mut dialog = app_handle.dialog().file()
+        .add_filter("Image Files", &extensions);
+
+    if let Some(dir) = resolve_dialog_start_dir(start_dir, &state) {
+        dialog = dialog.set_directory(dir);
+    }
+
+    dialog.pick_files(move |file_paths| {
+            if let Ok(mut sender) = tx.lock() {
+                if let Some(tx) = sender.take() {
+                    let _ = tx.send(file_paths);
+                }
+            }
+        });
+
+    let file_paths = await_dialog_response(rx).await?;
+    if let Some(first_path) = file_paths.as_ref().and_then(|paths| paths.first()) {
+        remember_dialog_dir(Path::new(&first_path.to_string()).parent(), &state);
+    }
+    for file_path in file_paths.iter().flatten() {
+        state.allowed_paths.allow(Path::new(&file_path.to_string()));
+    }
+
+    // The filter is only a hint on some platforms, so re-check the extensions
+    Ok(file_paths.map(|file_paths| {
+        file_paths.into_iter()
+            .map(|file_path| file_path.to_string())
+            .filter(|path| {
+                Path::new(path).extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| supported_extensions.contains(&ext.to_lowercase()))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }))
+}
+
+// Helper function to allow reads from the folders a loaded session's tabs point into
+fn allow_session_images(state: &AppState, session_data: &SessionData) {
+    for tab in &session_data.tabs {
+        state.allowed_paths.allow(Path::new(&tab.image_path));
+    }
+}
+
+// Helper function to refuse persisting a session without tabs (guards against overwriting a good file)
+fn ensure_session_has_tabs(session_data: &SessionData, allow_empty: Option<bool>) -> Result<(), String> {
+    if session_data.tabs.is_empty() && !allow_empty.unwrap_or(false) {
+        return Err("Refusing to save a session with no tabs (pass allowEmpty to save it anyway)".to_string());
+    }
+    Ok(())
+}
+
+// Default cap on tabs for create_session_from_folder; far beyond this the tab bar becomes unusable
+const DEFAULT_MAX_FOLDER_SESSION_TABS: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderSessionResult {
+    #[serde(rename = "sessionData")]
+    session_data: SessionData,
+    truncated: bool, // More images were found than max_tabs allowed
+    #[serde(rename = "totalCount")]
+    total_count: usize,
+}
+
+// Build an unsaved session with one tab per image in a folder, in browse order, up to max_tabs.
+// Zoom, fit and pan are left unset so the viewer applies its defaults.
+#[tauri::command]
+async fn create_session_from_folder(
+    path: String,
+    name: Option<String>,
+    max_tabs: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<FolderSessionResult, String> {
+    let folder = PathBuf::from(&path);
+    if !folder.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+
+    let entries = collect_image_files(&folder)?;
+    state.allowed_paths.allow(&folder);
+
+    let total_count = entries.len();
+    let max_tabs = max_tabs.unwrap_or(DEFAULT_MAX_FOLDER_SESSION_TABS);
+    if max_tabs == 0 {
+        return Err("max_tabs must be at least 1".to_string());
+    }
+
+    let tabs: Vec<SessionTab> = entries.into_iter()
+        .take(max_tabs)
+        .enumerate()
+        .map(|(index, entry)| SessionTab {
+            id: Uuid::new_v4().to_string(),
+            image_path: entry.path,
+            order: index as i32,
+            group_id: None,
+            zoom_level: None,
+            fit_mode: None,
+            pan_offset: None,
+        })
+        .collect();
+
+    let name = name.or_else(|| folder.file_name().map(|n| n.to_string_lossy().to_string()));
 
-    let mut dialog = app_handle.dialog().file()
-        .add_filter("Image Files", &extensions);
+    println!("Created session with {} of {} images from: {}", tabs.len(), total_count, path);
+    let session_data = SessionData {
+        name,
+        active_tab_id: tabs.first().map(|tab| tab.id.clone()),
+        tabs,
+        groups: None,
+        created_at: Utc::now().to_rfc3339(),
+        layout_position: None,
+        layout_size: None,
+        tree_collapsed: None,
+        controls_visible: None,
+        skip_corrupt_images: None,
+        loaded_session_name: None,
+        loaded_session_path: None,
+    };
 
-    if let Some(path_str) = starting_path {
-        let path = std::path::Path::new(&path_str);
-        if let Some(parent) = path.parent() {
-            dialog = dialog.set_directory(parent);
-        }
-        if let Some(filename) = path.file_name() {
-            dialog = dialog.set_file_name(filename.to_string_lossy());
-        }
-    }
+    Ok(FolderSessionResult {
+        session_data,
+        truncated: total_count > max_tabs,
+        total_count,
+    })
+}
 
-    dialog.pick_file(move |file_path| {
-            if let Ok(mut sender) = tx.lock() {
-                if let Some(tx) = sender.take() {
-                    let _ = tx.send(file_path);
-                }
-            }
-        });
-    
-    match rx.await {
-        Ok(Some(file_path)) => {
-            let path_str = file_path.to_string();
-            Ok(Some(path_str))
-        }
-        Ok(None) => Ok(None), // User cancelled the dialog
-        Err(_) => Err("Dialog operation failed".to_string()),
-    }
+// Helper function to serialize a session and write it atomically, returning the number of bytes written
+fn write_session_file(path: &Path, session_data: &SessionData) -> Result<u64, String> {
+    let json_data = serde_json::to_string_pretty(session_data)
+        .map_err(|e| format!("Failed to serialize session data: {}", e))?;
+    write_atomic(path, json_data.as_bytes())
+        .map_err(|e| format!("Failed to write session file: {}", e))?;
+    Ok(json_data.len() as u64)
 }
 
-#[tauri::command]
-async fn save_session_dialog(app_handle: tauri::AppHandle, session_data: SessionData, state: State<'_, AppState>) -> Result<Option<String>, String> {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedSessionFile {
+    path: String,
+    #[serde(rename = "bytesWritten")]
+    bytes_written: u64,
+}
+
+// Helper function to ask where to save a session, suggesting a file name from the session name
+async fn pick_session_save_path(app_handle: &tauri::AppHandle, session_data: &SessionData) -> Result<Option<PathBuf>, String> {
     use tauri_plugin_dialog::DialogExt;
     use std::sync::{Arc, Mutex};
     use tokio::sync::oneshot;
@@ -565,10 +1941,10 @@ async fn save_session_dialog(app_handle: tauri::AppHandle, session_data: Session
     let (tx, rx) = oneshot::channel();
     let tx = Arc::new(Mutex::new(Some(tx)));
 
-    // Create a default filename with timestamp if no name is provided
-    let default_name = match &session_data.name {
-        Some(name) => format!("{}.session.json", name),
-        None => {
+    // Create a default filename from the session name, or with a timestamp if there is no usable name
+    let default_name = match session_data.name.as_deref().map(sanitize_filename) {
+        Some(name) if !name.is_empty() => format!("{}.session.json", name),
+        _ => {
             let now = chrono::Utc::now();
             format!("session_{}.session.json", now.format("%Y%m%d_%H%M%S"))
         }
@@ -586,57 +1962,104 @@ async fn save_session_dialog(app_handle: tauri::AppHandle, session_data: Session
         });
 
     match rx.await {
-        Ok(Some(file_path)) => {
-            let path_buf = file_path.as_path().unwrap();
-            let path_str = path_buf.to_string_lossy().to_string();
+        Ok(Some(file_path)) => file_path.into_path()
+            .map(Some)
+            .map_err(|e| format!("Invalid session file path: {}", e)),
+        Ok(None) => Ok(None), // User cancelled the dialog
+        Err(_) => Err("Dialog operation failed".to_string()),
+    }
+}
 
-            // Serialize session data to JSON
-            let json_data = serde_json::to_string_pretty(&session_data)
-                .map_err(|e| format!("Failed to serialize session data: {}", e))?;
+#[tauri::command]
+async fn save_session_dialog(
+    app_handle: tauri::AppHandle,
+    session_data: SessionData,
+    allow_empty: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Option<SavedSessionFile>, String> {
+    ensure_session_has_tabs(&session_data, allow_empty)?;
 
-            // Write to file
-            std::fs::write(&path_buf, json_data)
-                .map_err(|e| format!("Failed to write session file: {}", e))?;
+    let Some(path_buf) = pick_session_save_path(&app_handle, &session_data).await? else {
+        return Ok(None);
+    };
+    let path_str = path_buf.to_string_lossy().to_string();
 
-            println!("Session saved to: {}", path_str);
+    let bytes_written = write_session_file(&path_buf, &session_data)?;
+    println!("Session saved to: {}", path_str);
 
-            // Add to recent sessions list and persist
-            add_recent_session(&state.recent_sessions, &path_str)?;
-            save_recent_sessions(&state.recent_sessions)?;
+    // Add to recent sessions list and persist
+    add_recent_session(&state.recent_sessions, &path_str)?;
+    save_recent_sessions(&state.recent_sessions)?;
 
-            // Set this as the currently loaded session
-            let path_obj = Path::new(&path_str);
-            let session_name = path_obj.file_stem()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown")
-                .to_string();
-            *state.loaded_session.lock().unwrap() = Some(LoadedSessionInfo {
-                name: session_name.clone(),
-                path: path_str.clone(),
-            });
+    // Set this as the currently loaded session
+    let session_name = path_buf.file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Unknown")
+        .to_string();
+    *state.loaded_session.lock().unwrap() = Some(LoadedSessionInfo {
+        name: session_name,
+        path: path_str.clone(),
+    });
+    sync_session_watcher(&app_handle, &state);
 
-            // Update window title to show loaded session
-            let window_title = format!("Image Viewer: {}", session_name);
-            if let Err(e) = set_window_title(app_handle.clone(), window_title).await {
-                eprintln!("Warning: Failed to update window title: {}", e);
-            }
+    // Update window title to show loaded session
+    if let Err(e) = refresh_window_title(&app_handle, &state) {
+        eprintln!("Warning: Failed to update window title: {}", e);
+    }
 
-            // Update the menu to reflect the new recent sessions list and loaded session
-            let recent_sessions = state.recent_sessions.lock().unwrap().clone();
-            let loaded_session = state.loaded_session.lock().unwrap().clone();
-            if let Err(e) = update_full_menu(&app_handle, &recent_sessions, &loaded_session) {
-                eprintln!("Warning: Failed to update menu: {}", e);
-            }
+    // Update the menu to reflect the new recent sessions list and loaded session
+    let recent_sessions = state.recent_sessions.lock().unwrap().clone();
+    let loaded_session = state.loaded_session.lock().unwrap().clone();
+    if let Err(e) = update_full_menu(&app_handle, &recent_sessions, &loaded_session) {
+        eprintln!("Warning: Failed to update menu: {}", e);
+    }
 
-            Ok(Some(path_str))
-        }
-        Ok(None) => Ok(None), // User cancelled the dialog
-        Err(_) => Err("Dialog operation failed".to_string()),
+    Ok(Some(SavedSessionFile {
+        path: path_str,
+        bytes_written,
+    }))
+}
+
+// Like save_session_dialog, but the loaded session, window title and loaded-session menu are left alone
+#[tauri::command]
+async fn save_session_copy_dialog(
+    app_handle: tauri::AppHandle,
+    session_data: SessionData,
+    allow_empty: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Option<SavedSessionFile>, String> {
+    ensure_session_has_tabs(&session_data, allow_empty)?;
+
+    let Some(path_buf) = pick_session_save_path(&app_handle, &session_data).await? else {
+        return Ok(None);
+    };
+    let path_str = path_buf.to_string_lossy().to_string();
+
+    let bytes_written = write_session_file(&path_buf, &session_data)?;
+    println!("Session copy saved to: {}", path_str);
+
+    add_recent_session(&state.recent_sessions, &path_str)?;
+    save_recent_sessions(&state.recent_sessions)?;
+
+    // Only the recent list changed; rebuild the menu with the current loaded session as-is
+    let recent_sessions = state.recent_sessions.lock().unwrap().clone();
+    let loaded_session = state.loaded_session.lock().unwrap().clone();
+    if let Err(e) = update_full_menu(&app_handle, &recent_sessions, &loaded_session) {
+        eprintln!("Warning: Failed to update menu: {}", e);
     }
+
+    Ok(Some(SavedSessionFile {
+        path: path_str,
+        bytes_written,
+    }))
 }
 
 #[tauri::command]
-async fn load_session_dialog(app_handle: tauri::AppHandle, state: State<'_, AppState>) -> Result<Option<LoadedSessionResult>, String> {
+async fn load_session_dialog(
+    app_handle: tauri::AppHandle,
+    resolve_images: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Option<LoadedSessionResult>, String> {
     use tauri_plugin_dialog::DialogExt;
     use std::sync::{Arc, Mutex};
     use tokio::sync::oneshot;
@@ -660,12 +2083,24 @@ async fn load_session_dialog(app_handle: tauri::AppHandle, state: State<'_, AppS
             let path_str = path_buf.to_string_lossy().to_string();
 
             // Read the file
-            let json_data = std::fs::read_to_string(&path_buf)
+            let json_data = std::fs::read_to_string(path_buf)
                 .map_err(|e| format!("Failed to read session file: {}", e))?;
 
             // Deserialize JSON data
             let session_data: SessionData = serde_json::from_str(&json_data)
                 .map_err(|e| format!("Failed to parse session data: {}", e))?;
+            allow_session_images(&state, &session_data);
+            session_paths::warn_on_path_drift(&session_data);
+
+            // Read every tab's image up front so broken tabs are reported together
+            let image_errors = if resolve_images.unwrap_or(false) {
+                session_image_errors(&session_data, &state).await
+            } else {
+                Vec::new()
+            };
+            if !image_errors.is_empty() {
+                println!("{} images in the session couldn't be loaded", image_errors.len());
+            }
 
             // Add to recent sessions list
             add_recent_session(&state.recent_sessions, &path_str)?;
@@ -680,10 +2115,10 @@ async fn load_session_dialog(app_handle: tauri::AppHandle, state: State<'_, AppS
                 name: session_name.clone(),
                 path: path_str.clone(),
             });
+            sync_session_watcher(&app_handle, &state);
 
             // Update window title to show loaded session
-            let window_title = format!("Image Viewer: {}", session_name);
-            if let Err(e) = set_window_title(app_handle.clone(), window_title).await {
+            if let Err(e) = refresh_window_title(&app_handle, &state) {
                 eprintln!("Warning: Failed to update window title: {}", e);
             }
 
@@ -699,6 +2134,7 @@ async fn load_session_dialog(app_handle: tauri::AppHandle, state: State<'_, AppS
                 session_data,
                 path: path_str,
                 name: session_name,
+                image_errors,
             }))
         }
         Ok(None) => Ok(None), // User cancelled the dialog
@@ -706,45 +2142,57 @@ async fn load_session_dialog(app_handle: tauri::AppHandle, state: State<'_, AppS
     }
 }
 
+// Payload of the `auto-session-saved` and `auto-session-loaded` events
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoSessionEvent {
+    profile: Option<String>,
+    path: String,
+    timestamp: String, // RFC 3339
+}
+
+// Helper function to resolve the auto-session file, optionally for a named profile
+fn get_auto_session_file(profile: Option<&str>) -> Result<PathBuf, String> {
+    let file_name = match profile {
+        Some(profile) => {
+            let profile = sanitize_filename(profile);
+            if profile.is_empty() {
+                return Err("Invalid auto-session profile name".to_string());
+            }
+            format!("auto-session-{}.json", profile)
+        }
+        None => "auto-session.json".to_string(),
+    };
+    Ok(get_app_data_dir()?.join(file_name))
+}
+
 #[tauri::command]
-async fn save_auto_session(session_data: SessionData) -> Result<(), String> {
+async fn save_auto_session(app_handle: tauri::AppHandle, session_data: SessionData, profile: Option<String>) -> Result<u64, String> {
     use std::fs;
-    use dirs;
-    
-    // Get the application data directory
-    let app_data_dir = dirs::data_dir()
-        .ok_or("Failed to get application data directory")?
-        .join("image-viewer");
-    
+
+    let session_file = get_auto_session_file(profile.as_deref())?;
+
     // Create the directory if it doesn't exist
-    fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
-    
-    let session_file = app_data_dir.join("auto-session.json");
-    
-    // Serialize session data to JSON
-    let json_data = serde_json::to_string_pretty(&session_data)
-        .map_err(|e| format!("Failed to serialize session data: {}", e))?;
-    
-    // Write to file
-    fs::write(&session_file, json_data)
-        .map_err(|e| format!("Failed to write session file: {}", e))?;
-    
-    println!("Auto-session saved to: {}", session_file.display());
-    Ok(())
+    if let Some(app_data_dir) = session_file.parent() {
+        fs::create_dir_all(app_data_dir)
+            .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    }
+
+    let bytes_written = write_session_file(&session_file, &session_data)?;
+
+    println!("Auto-session saved to: {} ({} bytes)", session_file.display(), bytes_written);
+    let _ = app_handle.emit("auto-session-saved", AutoSessionEvent {
+        profile,
+        path: session_file.to_string_lossy().to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+    });
+    Ok(bytes_written)
 }
 
 #[tauri::command]
-async fn load_auto_session() -> Result<Option<SessionData>, String> {
+async fn load_auto_session(app_handle: tauri::AppHandle, profile: Option<String>, state: State<'_, AppState>) -> Result<Option<SessionData>, String> {
     use std::fs;
-    use dirs;
 
-    // Get the application data directory
-    let app_data_dir = dirs::data_dir()
-        .ok_or("Failed to get application data directory")?
-        .join("image-viewer");
-
-    let session_file = app_data_dir.join("auto-session.json");
+    let session_file = get_auto_session_file(profile.as_deref())?;
 
     // Check if the session file exists
     if !session_file.exists() {
@@ -758,36 +2206,69 @@ async fn load_auto_session() -> Result<Option<SessionData>, String> {
     // Deserialize JSON data
     let session_data: SessionData = serde_json::from_str(&json_data)
         .map_err(|e| format!("Failed to parse session data: {}", e))?;
+    allow_session_images(&state, &session_data);
+    session_paths::warn_on_path_drift(&session_data);
 
     println!("Auto-session loaded from: {}", session_file.display());
+    let _ = app_handle.emit("auto-session-loaded", AutoSessionEvent {
+        profile,
+        path: session_file.to_string_lossy().to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+    });
     Ok(Some(session_data))
 }
 
+// Maximum number of manual sessions remembered in the recent list
+const MAX_RECENT_SESSIONS: usize = 10;
+
+// One entry in the recent sessions list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentSession {
+    path: String,
+    last_opened: Option<String>, // RFC 3339; None for entries carried over from the old path-only format
+}
+
+// recent-sessions.json used to be a plain array of paths, so accept either shape per entry
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredRecentSession {
+    Entry(RecentSession),
+    Path(String),
+}
+
+// Helper function to parse recent-sessions.json in either the current or the old format
+fn parse_recent_sessions(json_data: &str) -> Result<Vec<RecentSession>, serde_json::Error> {
+    let stored: Vec<StoredRecentSession> = serde_json::from_str(json_data)?;
+    Ok(stored.into_iter()
+        .map(|entry| match entry {
+            StoredRecentSession::Entry(session) => session,
+            StoredRecentSession::Path(path) => RecentSession { path, last_opened: None },
+        })
+        .collect())
+}
+
 // Helper function to add a session to the recent list (max 10 items)
-fn add_recent_session(recent_sessions: &Arc<Mutex<Vec<String>>>, path: &str) -> Result<(), String> {
+fn add_recent_session(recent_sessions: &Arc<Mutex<Vec<RecentSession>>>, path: &str) -> Result<(), String> {
     let mut sessions = recent_sessions.lock().unwrap();
 
     // Remove the path if it already exists (to move it to the front)
-    sessions.retain(|p| p != path);
+    sessions.retain(|session| !same_path(&session.path, path));
 
-    // Add to the front
-    sessions.insert(0, path.to_string());
+    // Add to the front, stamped with the time it was opened
+    sessions.insert(0, RecentSession {
+        path: path.to_string(),
+        last_opened: Some(Utc::now().to_rfc3339()),
+    });
 
     // Keep only the most recent 10
-    if sessions.len() > 10 {
-        sessions.truncate(10);
-    }
+    sessions.truncate(MAX_RECENT_SESSIONS);
 
     Ok(())
 }
 
 // Helper function to save recent sessions to disk
-fn save_recent_sessions(recent_sessions: &Arc<Mutex<Vec<String>>>) -> Result<(), String> {
-    use dirs;
-
-    let app_data_dir = dirs::data_dir()
-        .ok_or("Failed to get application data directory")?
-        .join("image-viewer");
+fn save_recent_sessions(recent_sessions: &Arc<Mutex<Vec<RecentSession>>>) -> Result<(), String> {
+    let app_data_dir = get_app_data_dir()?;
 
     fs::create_dir_all(&app_data_dir)
         .map_err(|e| format!("Failed to create app data directory: {}", e))?;
@@ -804,32 +2285,50 @@ fn save_recent_sessions(recent_sessions: &Arc<Mutex<Vec<String>>>) -> Result<(),
     Ok(())
 }
 
+// Outcome of reading recent-sessions.json at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentSessionsLoadStatus {
+    recovered_from_corruption: bool, // The file couldn't be parsed and the list was reset
+    backup_path: Option<String>, // Where the unreadable file was moved, if the move succeeded
+}
+
 // Helper function to load recent sessions from disk
-fn load_recent_sessions() -> Vec<String> {
-    use dirs;
+fn load_recent_sessions() -> (Vec<RecentSession>, RecentSessionsLoadStatus) {
+    let mut status = RecentSessionsLoadStatus::default();
 
-    let app_data_dir = match dirs::data_dir() {
-        Some(dir) => dir.join("image-viewer"),
-        None => return Vec::new(),
+    let app_data_dir = match get_app_data_dir() {
+        Ok(dir) => dir,
+        Err(_) => return (Vec::new(), status),
     };
 
     let recent_sessions_file = app_data_dir.join("recent-sessions.json");
 
     if !recent_sessions_file.exists() {
-        return Vec::new();
+        return (Vec::new(), status);
     }
 
-    match fs::read_to_string(&recent_sessions_file) {
+    let sessions = match fs::read_to_string(&recent_sessions_file) {
         Ok(json_data) => {
-            match serde_json::from_str::<Vec<String>>(&json_data) {
+            match parse_recent_sessions(&json_data) {
                 Ok(sessions) => {
                     // Validate that files still exist and filter out missing ones
                     sessions.into_iter()
-                        .filter(|path| Path::new(path).exists())
+                        .filter(|session| Path::new(&session.path).exists())
                         .collect()
                 }
                 Err(e) => {
                     eprintln!("Failed to parse recent sessions: {}", e);
+
+                    // Keep the damaged file for manual recovery instead of overwriting it on the next save
+                    let backup_file = app_data_dir.join("recent-sessions.corrupt.json");
+                    match fs::rename(&recent_sessions_file, &backup_file) {
+                        Ok(()) => {
+                            eprintln!("Moved unreadable recent sessions file to: {}", backup_file.display());
+                            status.backup_path = Some(backup_file.to_string_lossy().to_string());
+                        }
+                        Err(e) => eprintln!("Failed to back up recent sessions file: {}", e),
+                    }
+                    status.recovered_from_corruption = true;
                     Vec::new()
                 }
             }
@@ -838,13 +2337,208 @@ fn load_recent_sessions() -> Vec<String> {
             eprintln!("Failed to read recent sessions file: {}", e);
             Vec::new()
         }
+    };
+
+    (sessions, status)
+}
+
+#[tauri::command]
+async fn get_recent_sessions_load_status(state: State<'_, AppState>) -> Result<RecentSessionsLoadStatus, String> {
+    Ok(state.recent_sessions_load_status.clone())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppDataImportSummary {
+    recent_sessions_added: usize,
+    favorite_sessions_added: usize,
+    auto_session_files: Vec<String>, // File names as written to the app data directory
+    auto_sessions_renamed: Vec<RenamedAutoSession>,
+}
+
+// An auto-session imported under a new profile name because its own name was taken
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenamedAutoSession {
+    source: String,
+    imported_as: String,
+}
+
+// Helper function to read a JSON list of session paths, treating a missing file as empty
+fn read_session_path_list(file: &Path) -> Result<Vec<String>, String> {
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+    let json_data = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse {}: {}", file.display(), e))
+}
+
+// Helper function to read a recent-sessions.json file, treating a missing file as empty
+fn read_recent_session_list(file: &Path) -> Result<Vec<RecentSession>, String> {
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+    let json_data = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    parse_recent_sessions(&json_data)
+        .map_err(|e| format!("Failed to parse {}: {}", file.display(), e))
+}
+
+// Helper function to append recent sessions whose paths aren't already listed, stopping once the list
+// holds MAX_RECENT_SESSIONS; returns how many were added
+fn merge_recent_sessions(existing: &mut Vec<RecentSession>, incoming: Vec<RecentSession>) -> usize {
+    let mut added = 0;
+    for session in incoming {
+        if existing.len() >= MAX_RECENT_SESSIONS {
+            break;
+        }
+        if !existing.iter().any(|s| same_path(&s.path, &session.path)) && Path::new(&session.path).exists() {
+            existing.push(session);
+            added += 1;
+        }
+    }
+    added
+}
+
+// Helper function to append paths that aren't already in the list, returning how many were added
+fn merge_session_paths(existing: &mut Vec<String>, incoming: Vec<String>) -> usize {
+    let mut added = 0;
+    for path in incoming {
+        if !existing.iter().any(|existing_path| same_path(existing_path, &path)) && Path::new(&path).exists() {
+            existing.push(path);
+            added += 1;
+        }
+    }
+    added
+}
+
+// Helper function to pick the file an imported auto-session is written to: its own name when free,
+// otherwise the first unused `<profile>-imported` profile, so an existing auto-session is never overwritten
+fn auto_session_import_name(target_dir: &Path, file_name: &str) -> String {
+    if !target_dir.join(file_name).exists() {
+        return file_name.to_string();
+    }
+
+    let profile = file_name.strip_prefix("auto-session")
+        .and_then(|rest| rest.strip_suffix(".json"))
+        .unwrap_or("")
+        .trim_start_matches('-');
+    let base = if profile.is_empty() {
+        "imported".to_string()
+    } else {
+        format!("{}-imported", profile)
+    };
+
+    (1..)
+        .map(|n| match n {
+            1 => format!("auto-session-{}.json", base),
+            n => format!("auto-session-{}-{}.json", base, n),
+        })
+        .find(|name| !target_dir.join(name).exists())
+        .unwrap()
+}
+
+#[tauri::command]
+async fn import_app_data(app: tauri::AppHandle, source_dir: String, state: State<'_, AppState>) -> Result<AppDataImportSummary, String> {
+    let source_path = PathBuf::from(&source_dir);
+    if !source_path.is_dir() {
+        return Err(format!("Path is not a directory: {}", source_dir));
+    }
+
+    let recent_file = source_path.join("recent-sessions.json");
+    let favorites_file = source_path.join("favorite-sessions.json");
+    let auto_session_files: Vec<PathBuf> = fs::read_dir(&source_path)
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with("auto-session") && n.ends_with(".json"))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    // Make sure this is really an image viewer data directory before touching anything
+    if !recent_file.exists() && !favorites_file.exists() && auto_session_files.is_empty() {
+        return Err(format!("No image viewer data found in: {}", source_dir));
+    }
+
+    // Parse everything up front so a bad source file doesn't leave a partial import
+    let imported_recent = read_recent_session_list(&recent_file)?;
+    let imported_favorites = read_session_path_list(&favorites_file)?;
+    let mut imported_auto_session_data = Vec::new();
+    for source_file in auto_session_files {
+        let Some(file_name) = source_file.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let json_data = fs::read_to_string(&source_file)
+            .map_err(|e| format!("Failed to read {}: {}", source_file.display(), e))?;
+        serde_json::from_str::<SessionData>(&json_data)
+            .map_err(|e| format!("Failed to parse {}: {}", source_file.display(), e))?;
+        imported_auto_session_data.push((file_name.to_string(), json_data));
+    }
+
+    let app_data_dir = get_app_data_dir()?;
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    if fs::canonicalize(&source_path).ok() == fs::canonicalize(&app_data_dir).ok() {
+        return Err("Cannot import app data from the current app data directory".to_string());
+    }
+
+    // Merge recent sessions after the current ones, keeping the list capped
+    let recent_sessions_added = merge_recent_sessions(&mut state.recent_sessions.lock().unwrap(), imported_recent);
+    save_recent_sessions(&state.recent_sessions)?;
+
+    let favorite_sessions_added = if favorites_file.exists() {
+        let target_file = app_data_dir.join("favorite-sessions.json");
+        let mut favorites = read_session_path_list(&target_file)?;
+        let added = merge_session_paths(&mut favorites, imported_favorites);
+        let json_data = serde_json::to_string_pretty(&favorites)
+            .map_err(|e| format!("Failed to serialize favorite sessions: {}", e))?;
+        write_atomic(&target_file, json_data.as_bytes())
+            .map_err(|e| format!("Failed to write favorite sessions file: {}", e))?;
+        added
+    } else {
+        0
+    };
+
+    let mut imported_auto_sessions = Vec::new();
+    let mut auto_sessions_renamed = Vec::new();
+    for (file_name, json_data) in imported_auto_session_data {
+        let target_name = auto_session_import_name(&app_data_dir, &file_name);
+        write_atomic(&app_data_dir.join(&target_name), json_data.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", target_name, e))?;
+        if target_name != file_name {
+            auto_sessions_renamed.push(RenamedAutoSession {
+                source: file_name,
+                imported_as: target_name.clone(),
+            });
+        }
+        imported_auto_sessions.push(target_name);
+    }
+
+    // Update the menu to reflect the merged recent sessions list
+    let recent_sessions = state.recent_sessions.lock().unwrap().clone();
+    let loaded_session = state.loaded_session.lock().unwrap().clone();
+    if let Err(e) = update_full_menu(&app, &recent_sessions, &loaded_session) {
+        eprintln!("Warning: Failed to update menu: {}", e);
     }
+
+    println!("Imported app data from: {}", source_dir);
+    Ok(AppDataImportSummary {
+        recent_sessions_added,
+        favorite_sessions_added,
+        auto_session_files: imported_auto_sessions,
+        auto_sessions_renamed,
+    })
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RecentSessionInfo {
     path: String,
     name: String,
+    last_opened: Option<String>,
 }
 
 #[tauri::command]
@@ -852,21 +2546,133 @@ async fn get_recent_sessions(state: State<'_, AppState>) -> Result<Vec<RecentSes
     let sessions = state.recent_sessions.lock().unwrap();
 
     let mut result = Vec::new();
-    for path in sessions.iter() {
+    for session in sessions.iter() {
         // Extract filename from path
-        let path_obj = Path::new(path);
+        let path_obj = Path::new(&session.path);
         let name = path_obj.file_stem()
             .and_then(|n| n.to_str())
             .unwrap_or("Unknown")
             .to_string();
 
         result.push(RecentSessionInfo {
-            path: path.clone(),
+            path: session.path.clone(),
             name,
+            last_opened: session.last_opened.clone(),
+        });
+    }
+
+    Ok(result)
+}
+
+// Minimal view of a session file for listings: tabs are counted but not deserialized
+#[derive(Debug, Deserialize)]
+struct SessionHeader {
+    name: Option<String>,
+    #[serde(rename = "createdAt")]
+    created_at: Option<String>,
+    #[serde(default)]
+    tabs: Vec<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionSummary {
+    path: String,
+    name: String,
+    session_name: Option<String>,
+    created_at: Option<String>,
+    tab_count: Option<usize>,
+    error: Option<String>, // Set when the file could not be read or parsed
+}
+
+// Helper function to summarize a session file, recording failures instead of returning them
+fn summarize_session_file(path: &Path) -> SessionSummary {
+    let name = path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.trim_end_matches(".session.json").to_string())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let header = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read session file: {}", e))
+        .and_then(|json_data| {
+            serde_json::from_str::<SessionHeader>(&json_data)
+                .map_err(|e| format!("Failed to parse session data: {}", e))
         });
+
+    match header {
+        Ok(header) => SessionSummary {
+            path: path.to_string_lossy().to_string(),
+            name,
+            session_name: header.name,
+            created_at: header.created_at,
+            tab_count: Some(header.tabs.len()),
+            error: None,
+        },
+        Err(e) => SessionSummary {
+            path: path.to_string_lossy().to_string(),
+            name,
+            session_name: None,
+            created_at: None,
+            tab_count: None,
+            error: Some(e),
+        },
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionHeaderInfo {
+    name: Option<String>,
+    created_at: Option<String>,
+    tab_count: usize,
+}
+
+// Read just a session's name, creation time and tab count; tab contents are skipped, not deserialized
+#[tauri::command]
+async fn peek_session_header(path: String) -> Result<SessionHeaderInfo, String> {
+    let path_obj = Path::new(&path);
+    let json_data = fs::read_to_string(long_path(path_obj))
+        .map_err(|e| format!("Failed to read session file: {}", describe_io_error(&e, path_obj)))?;
+    let header: SessionHeader = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse session data: {}", e))?;
+
+    Ok(SessionHeaderInfo {
+        name: header.name,
+        created_at: header.created_at,
+        tab_count: header.tabs.len(),
+    })
+}
+
+// Comparable form of a path, so the frontend can dedupe tabs the same way the backend does
+#[tauri::command]
+async fn normalize_path(path: String) -> String {
+    paths::normalize_path(&path)
+}
+
+#[tauri::command]
+async fn list_sessions_in_dir(dir: String) -> Result<Vec<SessionSummary>, String> {
+    let dir_path = PathBuf::from(&dir);
+
+    if !dir_path.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir));
     }
 
-    Ok(result)
+    let dir_entries = fs::read_dir(&dir_path)
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    let mut summaries: Vec<SessionSummary> = dir_entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file() && path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.to_lowercase().ends_with(".session.json"))
+                .unwrap_or(false)
+        })
+        .map(|path| summarize_session_file(&path))
+        .collect();
+
+    summaries.sort_by(|a, b| natord::compare_ignore_case(&a.name, &b.name));
+
+    Ok(summaries)
 }
 
 #[tauri::command]
@@ -878,12 +2684,14 @@ async fn load_session_from_path(app: tauri::AppHandle, path: String, state: Stat
     }
 
     // Read the file
-    let json_data = fs::read_to_string(&path_obj)
+    let json_data = fs::read_to_string(path_obj)
         .map_err(|e| format!("Failed to read session file: {}", e))?;
 
     // Deserialize JSON data
     let session_data: SessionData = serde_json::from_str(&json_data)
         .map_err(|e| format!("Failed to parse session data: {}", e))?;
+    allow_session_images(&state, &session_data);
+    session_paths::warn_on_path_drift(&session_data);
 
     // Add to recent sessions list
     add_recent_session(&state.recent_sessions, &path)?;
@@ -895,13 +2703,13 @@ async fn load_session_from_path(app: tauri::AppHandle, path: String, state: Stat
         .unwrap_or("Unknown")
         .to_string();
     *state.loaded_session.lock().unwrap() = Some(LoadedSessionInfo {
-        name: session_name.clone(),
+        name: session_name,
         path: path.clone(),
     });
+    sync_session_watcher(&app, &state);
 
     // Update window title to show loaded session
-    let window_title = format!("Image Viewer: {}", session_name);
-    set_window_title(app.clone(), window_title).await?;
+    refresh_window_title(&app, &state)?;
 
     // Update the menu to reflect the new recent sessions list and loaded session
     let recent_sessions = state.recent_sessions.lock().unwrap().clone();
@@ -914,6 +2722,18 @@ async fn load_session_from_path(app: tauri::AppHandle, path: String, state: Stat
     Ok(session_data)
 }
 
+// Helper function to point the session file watcher at the loaded session, or stop it if none is loaded
+fn sync_session_watcher(app: &tauri::AppHandle, state: &AppState) {
+    match state.loaded_session.lock().unwrap().clone() {
+        Some(session_info) => {
+            if let Err(e) = state.session_watcher.watch(app, &session_info.path) {
+                eprintln!("Warning: Failed to watch loaded session file: {}", e);
+            }
+        }
+        None => state.session_watcher.stop(),
+    }
+}
+
 #[tauri::command]
 async fn refresh_menu(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     let recent_sessions = state.recent_sessions.lock().unwrap().clone();
@@ -925,12 +2745,12 @@ async fn refresh_menu(app: tauri::AppHandle, state: State<'_, AppState>) -> Resu
 
 #[tauri::command]
 async fn set_loaded_session(app: tauri::AppHandle, name: String, path: String, state: State<'_, AppState>) -> Result<(), String> {
-    let session_info = LoadedSessionInfo { name: name.clone(), path };
+    let session_info = LoadedSessionInfo { name, path };
     *state.loaded_session.lock().unwrap() = Some(session_info);
+    sync_session_watcher(&app, &state);
 
     // Update window title to show loaded session
-    let window_title = format!("Image Viewer: {}", name);
-    set_window_title(app.clone(), window_title).await?;
+    refresh_window_title(&app, &state)?;
 
     // Update menu to show the loaded session
     let recent_sessions = state.recent_sessions.lock().unwrap().clone();
@@ -944,9 +2764,10 @@ async fn set_loaded_session(app: tauri::AppHandle, name: String, path: String, s
 #[tauri::command]
 async fn clear_loaded_session(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     *state.loaded_session.lock().unwrap() = None;
+    sync_session_watcher(&app, &state);
 
-    // Reset window title to default
-    set_window_title(app.clone(), "Image Viewer".to_string()).await?;
+    // Reset window title to drop the session name
+    refresh_window_title(&app, &state)?;
 
     // Update menu to remove the loaded session
     let recent_sessions = state.recent_sessions.lock().unwrap().clone();
@@ -957,38 +2778,424 @@ async fn clear_loaded_session(app: tauri::AppHandle, state: State<'_, AppState>)
     Ok(())
 }
 
+// Helper function to drop a session from favorite-sessions.json, if it is listed there
+fn remove_favorite_session(path: &str) -> Result<(), String> {
+    let favorites_file = get_app_data_dir()?.join("favorite-sessions.json");
+    let mut favorites = read_session_path_list(&favorites_file)?;
+    let before = favorites.len();
+    favorites.retain(|favorite| !same_path(favorite, path));
+    if favorites.len() == before {
+        return Ok(());
+    }
+
+    let json_data = serde_json::to_string_pretty(&favorites)
+        .map_err(|e| format!("Failed to serialize favorite sessions: {}", e))?;
+    fs::write(&favorites_file, json_data)
+        .map_err(|e| format!("Failed to write favorite sessions file: {}", e))
+}
+
+// Move a saved session file to the trash and forget it everywhere the app remembers sessions
 #[tauri::command]
-async fn update_session_file(path: String, session_data: SessionData) -> Result<(), String> {
+async fn delete_session(app: tauri::AppHandle, path: String, state: State<'_, AppState>) -> Result<(), String> {
     let path_obj = Path::new(&path);
+    if !path_obj.is_file() {
+        return Err(format!("Session file does not exist: {}", path));
+    }
 
-    // Serialize session data to JSON
-    let json_data = serde_json::to_string_pretty(&session_data)
-        .map_err(|e| format!("Failed to serialize session data: {}", e))?;
+    let is_loaded = state.loaded_session.lock().unwrap().as_ref().is_some_and(|loaded| same_path(&loaded.path, &path));
+    if is_loaded {
+        // Flagged so the watcher doesn't report the file disappearing as an external edit
+        state.session_watcher.mark_self_write();
+    }
 
-    // Write to file
-    fs::write(&path_obj, json_data)
-        .map_err(|e| format!("Failed to write session file: {}", e))?;
+    trash::delete(path_obj).map_err(|e| format!("Failed to move session to trash: {}", e))?;
 
-    println!("Session file updated at: {}", path);
+    state.recent_sessions.lock().unwrap().retain(|session| !same_path(&session.path, &path));
+    save_recent_sessions(&state.recent_sessions)?;
+    if let Err(e) = remove_favorite_session(&path) {
+        eprintln!("Warning: Failed to update favorite sessions: {}", e);
+    }
+
+    if is_loaded {
+        // Also rebuilds the menu
+        clear_loaded_session(app.clone(), state.clone()).await?;
+    } else {
+        let recent_sessions = state.recent_sessions.lock().unwrap().clone();
+        let loaded_session = state.loaded_session.lock().unwrap().clone();
+        if let Err(e) = update_full_menu(&app, &recent_sessions, &loaded_session) {
+            eprintln!("Warning: Failed to update menu: {}", e);
+        }
+    }
+
+    println!("Session moved to trash: {}", path);
+    let _ = app.emit("session-deleted", path);
     Ok(())
 }
 
+// Rename a saved session file within its directory, keeping the recent list and loaded session pointing at it
+#[tauri::command]
+async fn rename_session(app: tauri::AppHandle, old_path: String, new_name: String, state: State<'_, AppState>) -> Result<String, String> {
+    let old_path_obj = Path::new(&old_path);
+    if !old_path_obj.is_file() {
+        return Err(format!("Session file does not exist: {}", old_path));
+    }
+
+    // Only accept names that are already valid filenames, rather than silently changing them
+    let new_name = new_name.trim();
+    let sanitized = sanitize_filename(new_name);
+    if sanitized.is_empty() || sanitized != new_name {
+        return Err(format!("Invalid session name: {}", new_name));
+    }
+
+    let new_path_obj = old_path_obj.with_file_name(format!("{}.session.json", sanitized));
+    let new_path = new_path_obj.to_string_lossy().to_string();
+
+    // A case-only rename on a case-insensitive filesystem "exists" but is the same file
+    if new_path_obj.exists() && canonicalize_path(&new_path_obj) != canonicalize_path(old_path_obj) {
+        return Err(format!("A file with that name already exists: {}", new_path));
+    }
+
+    // Flagged so the watcher doesn't report the old file disappearing as an external edit
+    state.session_watcher.mark_self_write();
+    fs::rename(long_path(old_path_obj), long_path(&new_path_obj))
+        .map_err(|e| format!("Failed to rename session file: {}", describe_io_error(&e, old_path_obj)))?;
+
+    // Point the recent entry at the new path, keeping its position and timestamp
+    {
+        let mut sessions = state.recent_sessions.lock().unwrap();
+        for session in sessions.iter_mut().filter(|session| same_path(&session.path, &old_path)) {
+            session.path = new_path.clone();
+        }
+    }
+    save_recent_sessions(&state.recent_sessions)?;
+
+    let is_loaded = state.loaded_session.lock().unwrap().as_ref().is_some_and(|loaded| same_path(&loaded.path, &old_path));
+    if is_loaded {
+        let session_name = new_path_obj.file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        *state.loaded_session.lock().unwrap() = Some(LoadedSessionInfo {
+            name: session_name,
+            path: new_path.clone(),
+        });
+        sync_session_watcher(&app, &state);
+
+        if let Err(e) = refresh_window_title(&app, &state) {
+            eprintln!("Warning: Failed to update window title: {}", e);
+        }
+    }
+
+    let recent_sessions = state.recent_sessions.lock().unwrap().clone();
+    let loaded_session = state.loaded_session.lock().unwrap().clone();
+    if let Err(e) = update_full_menu(&app, &recent_sessions, &loaded_session) {
+        eprintln!("Warning: Failed to update menu: {}", e);
+    }
+
+    println!("Session renamed from {} to {}", old_path, new_path);
+    Ok(new_path)
+}
+
+#[tauri::command]
+async fn update_session_file(
+    path: String,
+    session_data: SessionData,
+    allow_empty: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<u64, String> {
+    ensure_session_has_tabs(&session_data, allow_empty)?;
+
+    // Write to file (flagged so the session watcher doesn't report our own save as an external edit)
+    state.session_watcher.mark_self_write();
+    let bytes_written = write_session_file(Path::new(&path), &session_data)?;
+
+    println!("Session file updated at: {}", path);
+    Ok(bytes_written)
+}
+
 #[tauri::command]
 async fn set_window_title(app: tauri::AppHandle, title: String) -> Result<(), String> {
-    // Get the main window and set its title
+    apply_window_title(&app, &title)
+}
+
+// Helper function to set the title of every window
+fn apply_window_title(app: &tauri::AppHandle, title: &str) -> Result<(), String> {
     for (_, window) in app.webview_windows() {
-        window.set_title(&title)
+        window.set_title(title)
             .map_err(|e| format!("Failed to set window title: {}", e))?;
     }
     Ok(())
 }
 
+// Build the window title from the template. Placeholders without a value are left empty and
+// the separators around them trimmed, so "Image Viewer: {session}" becomes "Image Viewer" with no session.
+fn compute_window_title(template: &str, session: Option<&str>, image: Option<&str>) -> String {
+    const SEPARATORS: &[char] = &[' ', ':', '-', '|', '\u{2014}', '\u{00b7}'];
+
+    let title = template
+        .replace("{session}", session.unwrap_or(""))
+        .replace("{image}", image.unwrap_or(""));
+    let title = title.trim_matches(SEPARATORS);
+
+    if title.is_empty() {
+        "Image Viewer".to_string()
+    } else {
+        title.to_string()
+    }
+}
+
+// Helper function to recompute the window title from the loaded session and active image
+fn refresh_window_title(app: &tauri::AppHandle, state: &AppState) -> Result<(), String> {
+    let template = state.settings.lock().unwrap().title_template.clone();
+    let session_name = state.loaded_session.lock().unwrap().as_ref().map(|session| session.name.clone());
+    let active_image = state.active_image.lock().unwrap().clone();
+
+    let title = compute_window_title(&template, session_name.as_deref(), active_image.as_deref());
+    apply_window_title(app, &title)
+}
+
+#[tauri::command]
+async fn set_title_template(app: tauri::AppHandle, template: String, state: State<'_, AppState>) -> Result<(), String> {
+    update_settings(&state.settings, |settings| settings.title_template = template)?;
+    refresh_window_title(&app, &state)
+}
+
+#[tauri::command]
+async fn set_active_image(app: tauri::AppHandle, name: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    *state.active_image.lock().unwrap() = name;
+    refresh_window_title(&app, &state)
+}
+
+#[tauri::command]
+async fn set_cache_max_entries(max_entries: usize, state: State<'_, AppState>) -> Result<(), String> {
+    state.metadata_cache.set_max_entries(max_entries)?;
+    update_settings(&state.settings, |settings| settings.cache_max_entries = max_entries)?;
+    println!("Metadata cache limit set to {} entries", max_entries);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_cache_eviction_target_ratio(ratio: f64, state: State<'_, AppState>) -> Result<(), String> {
+    state.metadata_cache.set_eviction_target_ratio(ratio)?;
+    update_settings(&state.settings, |settings| settings.cache_eviction_target_ratio = ratio)
+}
+
+#[tauri::command]
+async fn flush_metadata_cache(state: State<'_, AppState>) -> Result<(), String> {
+    // Checkpoint the WAL so recent cache writes survive a hard quit
+    state.metadata_cache.flush()
+}
+
+// Dump the cached image metadata to a JSON file for backup or inspection, returning the row count
+#[tauri::command]
+async fn export_cache(dest: String, state: State<'_, AppState>) -> Result<usize, String> {
+    use std::io::{BufWriter, Write};
+
+    let dest_path = Path::new(&dest);
+    let file = fs::File::create(long_path(dest_path))
+        .map_err(|e| format!("Failed to create export file: {}", describe_io_error(&e, dest_path)))?;
+    let mut writer = BufWriter::new(file);
+
+    // Rows are streamed to the file, so even a large cache never becomes one giant string
+    let result = state.metadata_cache.export_all(&mut writer)
+        .and_then(|count| writer.flush().map(|_| count).map_err(|e| format!("Failed to write cache export: {}", e)));
+    if result.is_err() {
+        drop(writer);
+        let _ = fs::remove_file(long_path(dest_path));
+    }
+
+    let count = result?;
+    println!("Exported {} cache entries to {}", count, dest);
+    Ok(count)
+}
+
+// Load a file written by export_cache into the metadata cache, returning the number of rows imported
+#[tauri::command]
+async fn import_cache(src: String, state: State<'_, AppState>) -> Result<usize, String> {
+    let src_path = Path::new(&src);
+    let file = fs::File::open(long_path(src_path))
+        .map_err(|e| format!("Failed to open cache export: {}", describe_io_error(&e, src_path)))?;
+    // Rows are inserted in batches as they're parsed; a malformed file keeps the batches before the error
+    let count = state.metadata_cache.import_all(std::io::BufReader::new(file))?;
+    println!("Imported {} cache entries from {}", count, src);
+    Ok(count)
+}
+
+// Images most recently opened with read_image_file, across all folders; files that no longer exist are skipped.
+// Bulk reads (batch loads, aspect filtering) only bump the LRU timestamp and don't count as views.
+#[tauri::command]
+async fn get_recently_viewed_images(limit: usize, state: State<'_, AppState>) -> Result<Vec<metadata_cache::RecentlyViewed>, String> {
+    let mut recent = Vec::new();
+    let mut offset = 0;
+
+    // Read in pages until enough existing files are found, so deleted images don't shorten the list
+    while recent.len() < limit {
+        let page = state.metadata_cache.recently_viewed(limit, offset)?;
+        let page_len = page.len();
+        offset += page_len;
+
+        recent.extend(page.into_iter().filter(|image| Path::new(&image.path).is_file()));
+        if page_len < limit {
+            break;
+        }
+    }
+
+    recent.truncate(limit);
+    Ok(recent)
+}
+
+#[tauri::command]
+async fn print_image(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    is_path_allowed(&state, &path)?;
+    let image_path = Path::new(&path);
+
+    if !image_path.is_file() {
+        return Err(format!("Image file does not exist: {}", path));
+    }
+
+    shell::print_file(image_path)?;
+    println!("Sent to printer: {}", path);
+    Ok(())
+}
+
+#[tauri::command]
+async fn copy_image_to_clipboard(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    is_path_allowed(&state, &path)?;
+    let _permit = acquire_decode_permit(&state.decode_permits).await?;
+    let source = path.clone();
+    let rgba = tokio::task::spawn_blocking(move || {
+        let image = ImageReader::open(&source)
+            .map_err(|e| format!("Failed to open image file: {}", e))?
+            .with_guessed_format()
+            .map_err(|e| format!("Failed to detect image format: {}", e))?
+            .decode()
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+        // arboard expects tightly packed 8-bit RGBA regardless of the source format
+        Ok::<_, String>(image.to_rgba8())
+    })
+    .await
+    .map_err(|e| format!("Decode task failed: {}", e))??;
+    let (width, height) = rgba.dimensions();
+
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    clipboard
+        .set_image(arboard::ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: rgba.into_raw().into(),
+        })
+        .map_err(|e| format!("Failed to copy image to clipboard: {}", e))?;
+
+    println!("Copied image to clipboard: {}", path);
+    Ok(())
+}
+
+#[tauri::command]
+async fn extract_frame(path: String, frame_index: u32, dest: String, state: State<'_, AppState>) -> Result<ImageDimensions, String> {
+    is_path_allowed(&state, &path)?;
+    let _permit = acquire_decode_permit(&state.decode_permits).await?;
+
+    // Decoding frames is CPU-bound, so it runs off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let buffer = preview::decode_frame(Path::new(&path), frame_index)?;
+        buffer.save_with_format(&dest, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to write frame: {}", e))?;
+
+        println!("Extracted frame {} of {} to {}", frame_index, path, dest);
+        Ok(ImageDimensions {
+            width: buffer.width(),
+            height: buffer.height(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Frame task failed: {}", e))?
+}
+
+// Blurhash only captures low-frequency color, so encoding from a small copy loses nothing
+const BLURHASH_SAMPLE_SIZE: u32 = 64;
+
+#[tauri::command]
+async fn get_blurhash(path: String, components_x: u32, components_y: u32, state: State<'_, AppState>) -> Result<String, String> {
+    if !(1..=9).contains(&components_x) || !(1..=9).contains(&components_y) {
+        return Err("Blurhash components must be between 1 and 9".to_string());
+    }
+
+    is_path_allowed(&state, &path)?;
+
+    let metadata = fs::metadata(&path)
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    let last_modified = format_last_modified(&metadata)?;
+
+    if let Some(hash) = state.metadata_cache.get_blurhash(&path, &last_modified, components_x, components_y)? {
+        return Ok(hash);
+    }
+
+    let _permit = acquire_decode_permit(&state.decode_permits).await?;
+    let source = path.clone();
+    let hash = tokio::task::spawn_blocking(move || {
+        let image = ImageReader::open(&source)
+            .map_err(|e| format!("Failed to open image file: {}", e))?
+            .with_guessed_format()
+            .map_err(|e| format!("Failed to detect image format: {}", e))?
+            .decode()
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+        let sample = image.thumbnail(BLURHASH_SAMPLE_SIZE, BLURHASH_SAMPLE_SIZE).to_rgba8();
+        blurhash::encode(components_x, components_y, sample.width(), sample.height(), sample.as_raw())
+            .map_err(|e| format!("Failed to compute blurhash: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Blurhash task failed: {}", e))??;
+
+    state.metadata_cache.set_blurhash(&path, &last_modified, components_x, components_y, &hash)?;
+    Ok(hash)
+}
+
+#[tauri::command]
+async fn copy_path_to_clipboard(path: String) -> Result<String, String> {
+    // dunce keeps Windows paths in their familiar form instead of the \\?\ UNC variant
+    let canonical_path = dunce::canonicalize(&path)
+        .map_err(|e| format!("Failed to resolve path: {}", e))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    clipboard
+        .set_text(canonical_path.clone())
+        .map_err(|e| format!("Failed to copy path to clipboard: {}", e))?;
+
+    Ok(canonical_path)
+}
+
+// Where settings, sessions and the cache live this run, after any fallback (see get_app_data_dir)
+#[tauri::command]
+async fn get_app_data_path() -> Result<String, String> {
+    Ok(get_app_data_dir()?.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn open_app_data_dir() -> Result<String, String> {
+    let app_data_dir = get_app_data_dir()?;
+
+    // Create the directory if it doesn't exist yet so there is something to open
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    shell::open_in_file_manager(&app_data_dir)?;
+
+    Ok(app_data_dir.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 async fn exit_app(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     println!("Exiting application...");
 
     // Set the exiting flag so window close events won't prevent close
     *state.is_exiting.lock().unwrap() = true;
+    state.exit_watchdog.disarm();
 
     // Flush metadata cache to ensure all data is written to disk
     if let Ok(stats) = state.metadata_cache.get_stats() {
@@ -1008,19 +3215,43 @@ async fn exit_app(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(
     Ok(())
 }
 
+/// Start the exit watchdog once the frontend has decided to close and begins its final save.
+/// Called after any "save changes?" prompt, so the user can take as long as they like to answer;
+/// only a save that hangs past exit_timeout_secs gets the process force-exited.
+#[tauri::command]
+async fn begin_exit_save(state: State<'_, AppState>) -> Result<(), String> {
+    let timeout_secs = state.settings.lock().unwrap().exit_timeout_secs;
+    if timeout_secs > 0 {
+        state.exit_watchdog.arm(std::time::Duration::from_secs(timeout_secs), state.metadata_cache.clone());
+    }
+    Ok(())
+}
+
+/// Abort a close the user backed out of. Closing a window runs in three steps:
+///
+/// 1. CloseRequested is intercepted and the frontend gets tauri://close-requested
+/// 2. The frontend decides, e.g. by asking "save changes?", then calls begin_exit_save and saves the session
+/// 3. It then calls exit_app to close for real, or cancel_exit to keep the app open
+///
+/// The window was never closed, so this only has to make sure a later close is intercepted again.
+#[tauri::command]
+async fn cancel_exit(state: State<'_, AppState>) -> Result<(), String> {
+    println!("Exit cancelled, keeping the application open");
+    *state.is_exiting.lock().unwrap() = false;
+    state.exit_watchdog.disarm();
+    Ok(())
+}
+
 #[tauri::command]
 async fn launch_new_instance(session_data: SessionData) -> Result<(), String> {
     use std::process::Command;
-    use dirs;
 
     // Get the path to the current executable
     let current_exe = std::env::current_exe()
         .map_err(|e| format!("Failed to get current executable path: {}", e))?;
 
     // Get the application data directory (same as auto-session)
-    let app_data_dir = dirs::data_dir()
-        .ok_or("Failed to get application data directory")?
-        .join("image-viewer");
+    let app_data_dir = get_app_data_dir()?;
 
     // Create the directory if it doesn't exist
     fs::create_dir_all(&app_data_dir)
@@ -1033,8 +3264,8 @@ async fn launch_new_instance(session_data: SessionData) -> Result<(), String> {
         .map_err(|e| format!("Failed to serialize session data: {}", e))?;
 
     // Write to file
-    fs::write(&derivative_session_path, json_data)
-        .map_err(|e| format!("Failed to write derivative session file: {}", e))?;
+    fs::write(long_path(&derivative_session_path), json_data)
+        .map_err(|e| format!("Failed to write derivative session file: {}", describe_io_error(&e, &derivative_session_path)))?;
 
     println!("Derivative session saved to: {}", derivative_session_path.display());
 
@@ -1048,13 +3279,9 @@ async fn launch_new_instance(session_data: SessionData) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn load_derivative_session() -> Result<Option<SessionData>, String> {
-    use dirs;
-
+async fn load_derivative_session(state: State<'_, AppState>) -> Result<Option<SessionData>, String> {
     // Get the application data directory (same as auto-session)
-    let app_data_dir = dirs::data_dir()
-        .ok_or("Failed to get application data directory")?
-        .join("image-viewer");
+    let app_data_dir = get_app_data_dir()?;
 
     let derivative_session_path = app_data_dir.join("derivative-session.session.json");
 
@@ -1070,6 +3297,8 @@ async fn load_derivative_session() -> Result<Option<SessionData>, String> {
     // Deserialize JSON data
     let session_data: SessionData = serde_json::from_str(&json_data)
         .map_err(|e| format!("Failed to parse derivative session data: {}", e))?;
+    allow_session_images(&state, &session_data);
+    session_paths::warn_on_path_drift(&session_data);
 
     // Delete the file immediately after successful load
     fs::remove_file(&derivative_session_path)
@@ -1089,7 +3318,7 @@ async fn update_skip_corrupt_menu_state(app_handle: tauri::AppHandle, checked: b
         .ok_or("Failed to get menu")?;
 
     // Find the "toggle_skip_corrupt" menu item
-    let menu_item = menu.get("toggle_skip_corrupt")
+    let menu_item = menu.get(&MenuAction::ToggleSkipCorrupt.id())
         .ok_or("Failed to find toggle_skip_corrupt menu item")?;
 
     // Update the checked state
@@ -1104,31 +3333,30 @@ async fn update_skip_corrupt_menu_state(app_handle: tauri::AppHandle, checked: b
 }
 
 // Helper function to build the Recent Sessions submenu
-fn build_recent_sessions_submenu(app: &tauri::AppHandle, recent_sessions: &[String]) -> Result<tauri::menu::Submenu<tauri::Wry>, tauri::Error> {
+fn build_recent_sessions_submenu(app: &tauri::AppHandle, recent_sessions: &[RecentSession]) -> Result<tauri::menu::Submenu<tauri::Wry>, tauri::Error> {
     use tauri::menu::SubmenuBuilder;
-    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 
     let mut recent_menu_builder = SubmenuBuilder::new(app, "Recent Saved Sessions");
 
     // Add "Last Autosaved Session" at the top
     recent_menu_builder = recent_menu_builder
-        .text("load_auto_session_menu", "Last Autosaved Session");
+        .text(MenuAction::LoadAutoSession.id(), "Last Autosaved Session");
 
     if !recent_sessions.is_empty() {
         recent_menu_builder = recent_menu_builder.separator();
 
         // Add up to 10 recent manual sessions
-        for session_path in recent_sessions.iter().take(10) {
+        for session in recent_sessions.iter().take(10) {
+            let session_path = &session.path;
             let path_obj = Path::new(session_path);
             let name = path_obj.file_stem()
                 .and_then(|n| n.to_str())
                 .unwrap_or("Unknown")
                 .to_string();
 
-            // Encode the full path in the menu ID (base64 to handle special characters)
-            let encoded_path = URL_SAFE_NO_PAD.encode(session_path.as_bytes());
-            let menu_id = format!("load_recent_path_{}", encoded_path);
-            recent_menu_builder = recent_menu_builder.text(&menu_id, name);
+            // The full path travels in the menu ID
+            let menu_id = MenuAction::LoadRecentSession(session_path.clone()).id();
+            recent_menu_builder = recent_menu_builder.text(menu_id, name);
         }
     }
 
@@ -1151,8 +3379,8 @@ fn build_loaded_session_menu(app: &tauri::AppHandle, loaded_session: &Option<Loa
 
         println!("Building loaded session menu with name: '{}' (length: {})", session_info.name, session_info.name.len());
         let loaded_menu = SubmenuBuilder::new(app, menu_title)
-            .text("reload_session", "Reload")
-            .text("update_session", "Update")
+            .text(MenuAction::ReloadSession.id(), "Reload")
+            .text(MenuAction::UpdateSession.id(), "Update")
             .build()?;
         Ok(Some(loaded_menu))
     } else {
@@ -1160,30 +3388,70 @@ fn build_loaded_session_menu(app: &tauri::AppHandle, loaded_session: &Option<Loa
     }
 }
 
-// Update the menu with current recent sessions and loaded session
-fn update_full_menu(app: &tauri::AppHandle, recent_sessions: &[String], loaded_session: &Option<LoadedSessionInfo>) -> Result<(), String> {
-    use tauri::menu::{MenuBuilder, SubmenuBuilder, PredefinedMenuItem};
+// Helper function to build the View submenu (check items reflect the persisted settings)
+fn build_view_menu(app: &tauri::AppHandle) -> Result<tauri::menu::Submenu<tauri::Wry>, tauri::Error> {
+    use tauri::menu::CheckMenuItemBuilder;
+
+    let always_on_top = app.state::<AppState>().settings.lock().unwrap().always_on_top;
+    let always_on_top_item = CheckMenuItemBuilder::with_id(MenuAction::ToggleAlwaysOnTop.id(), "Always on Top")
+        .checked(always_on_top)
+        .build(app)?;
+
+    SubmenuBuilder::new(app, "View")
+        .text(MenuAction::ToggleControls.id(), "Toggle Controls")
+        .text(MenuAction::ToggleFullscreen.id(), "Enter Fullscreen")
+        .check(MenuAction::ToggleSkipCorrupt.id(), "Skip Corrupt Images")
+        .item(&always_on_top_item)
+        .separator()
+        .text(MenuAction::CopyPath.id(), "Copy Image Path")
+        .build()
+}
 
-    // Build the new recent sessions submenu
-    let recent_menu = build_recent_sessions_submenu(app, recent_sessions)
-        .map_err(|e| format!("Failed to build recent sessions submenu: {}", e))?;
+// Helper function to build the File submenu with the Recent Saved Sessions list
+fn build_file_menu(app: &tauri::AppHandle, recent_sessions: &[RecentSession]) -> Result<tauri::menu::Submenu<tauri::Wry>, tauri::Error> {
+    let recent_menu = build_recent_sessions_submenu(app, recent_sessions)?;
 
-    // Rebuild the entire menu with the updated submenu
-    let file_menu = SubmenuBuilder::new(app, "File")
-        .text("save_session", "Save Session")
-        .text("load_session", "Load Session")
+    SubmenuBuilder::new(app, "File")
+        .text(MenuAction::SaveSession.id(), "Save Session")
+        .text(MenuAction::LoadSession.id(), "Load Session")
         .item(&recent_menu)
         .separator()
-        .item(&PredefinedMenuItem::close_window(app, Some("Close Window"))
-            .map_err(|e| format!("Failed to create close window menu item: {}", e))?)
+        // Keep the platform-native Close Window (Cmd/Ctrl+W etc.)
+        .item(&PredefinedMenuItem::close_window(app, Some("Close Window"))?)
         .build()
+}
+
+// Helper function to apply the always-on-top preference to every window
+fn apply_always_on_top(app: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    for (_, window) in app.webview_windows() {
+        window.set_always_on_top(enabled)
+            .map_err(|e| format!("Failed to set always on top: {}", e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_always_on_top(app: tauri::AppHandle, enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
+    use tauri::menu::MenuItemKind;
+
+    apply_always_on_top(&app, enabled)?;
+    update_settings(&state.settings, |settings| settings.always_on_top = enabled)?;
+
+    // Keep the menu check mark in sync when toggled from elsewhere
+    if let Some(MenuItemKind::Check(check_item)) = app.menu().and_then(|menu| menu.get(&MenuAction::ToggleAlwaysOnTop.id())) {
+        let _ = check_item.set_checked(enabled);
+    }
+
+    Ok(())
+}
+
+// Update the menu with current recent sessions and loaded session
+fn update_full_menu(app: &tauri::AppHandle, recent_sessions: &[RecentSession], loaded_session: &Option<LoadedSessionInfo>) -> Result<(), String> {
+    // Rebuild the entire menu with the updated recent sessions
+    let file_menu = build_file_menu(app, recent_sessions)
         .map_err(|e| format!("Failed to build File menu: {}", e))?;
 
-    let view_menu = SubmenuBuilder::new(app, "View")
-        .text("toggle_controls", "Toggle Controls")
-        .text("toggle_fullscreen", "Enter Fullscreen")
-        .check("toggle_skip_corrupt", "Skip Corrupt Images")
-        .build()
+    let view_menu = build_view_menu(app)
         .map_err(|e| format!("Failed to build View menu: {}", e))?;
 
     // Build menu bar with File, View, and optionally Loaded Session
@@ -1209,11 +3477,38 @@ fn update_full_menu(app: &tauri::AppHandle, recent_sessions: &[String], loaded_s
 
 // Menu functionality will be implemented separately
 
+// Command-line flag and environment variable that switch the metadata cache to memory only
+const EPHEMERAL_FLAG: &str = "--ephemeral";
+const EPHEMERAL_ENV_VAR: &str = "IMAGE_VIEWER_EPHEMERAL";
+
+// Helper function to check whether this run asked for an in-memory metadata cache
+fn ephemeral_cache_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == EPHEMERAL_FLAG)
+        || std::env::var(EPHEMERAL_ENV_VAR).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let settings = AppSettings::load();
+
     // Initialize metadata cache
-    let metadata_cache = match MetadataCache::new(100_000) {
+    let cache_max_entries = settings.cache_max_entries.max(metadata_cache::MIN_MAX_ENTRIES);
+    let opened_cache = match &settings.cache_db_path {
+        // Ephemeral runs keep nothing about the images they open on disk
+        _ if ephemeral_cache_requested() => MetadataCache::in_memory(cache_max_entries),
+        Some(db_path) => MetadataCache::with_path(PathBuf::from(db_path), cache_max_entries).or_else(|e| {
+            // A missing SSD or unmounted drive shouldn't keep the app from starting
+            eprintln!("Failed to open metadata cache at {}: {}", db_path, e);
+            eprintln!("Falling back to the default cache location");
+            MetadataCache::new(cache_max_entries)
+        }),
+        None => MetadataCache::new(cache_max_entries),
+    };
+    let metadata_cache = match opened_cache {
         Ok(cache) => {
+            if let Err(e) = cache.set_eviction_target_ratio(settings.cache_eviction_target_ratio) {
+                eprintln!("Warning: Ignoring cache eviction setting: {}", e);
+            }
             if let Ok(stats) = cache.get_stats() {
                 println!("Metadata cache loaded: {}/{} entries", stats.entry_count, stats.max_entries);
             }
@@ -1227,15 +3522,30 @@ pub fn run() {
         }
     };
 
+    let decode_concurrency = match settings.max_concurrent_decodes {
+        0 => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+        limit => limit,
+    };
+    println!("Decoding up to {} images at once", decode_concurrency);
+
     // Initialize app state
-    let recent_sessions = load_recent_sessions();
+    let (recent_sessions, recent_sessions_load_status) = load_recent_sessions();
     println!("Loaded {} recent sessions", recent_sessions.len());
 
     let app_state = AppState {
         is_exiting: Arc::new(Mutex::new(false)),
+        exit_watchdog: Arc::new(ExitWatchdog::new()),
         metadata_cache,
         recent_sessions: Arc::new(Mutex::new(recent_sessions)),
+        recent_sessions_load_status,
         loaded_session: Arc::new(Mutex::new(None)), // No session loaded initially
+        settings: Arc::new(Mutex::new(settings)),
+        session_watcher: Arc::new(SessionWatcher::new()),
+        active_image: Arc::new(Mutex::new(None)),
+        file_operations: Arc::new(Mutex::new(Vec::new())),
+        operations: Arc::new(OperationRegistry::new()),
+        decode_permits: Arc::new(Semaphore::new(decode_concurrency)),
+        allowed_paths: Arc::new(PathAllowlist::new()),
     };
 
     tauri::Builder::default()
@@ -1245,53 +3555,117 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             browse_folder,
             browse_folder_paginated,
+            list_subfolders,
+            normalize_path,
+            browse_folder_streaming,
+            get_folder_signature,
+            set_default_page_size,
+            get_last_folder,
+            set_reopen_last_folder,
+            set_warm_cache_on_startup,
             get_folder_image_count,
+            get_folder_megapixels,
+            get_format_breakdown,
+            get_largest_images,
             read_image_file,
+            refresh_image_file,
             read_image_files_batch,
+            resolve_session_images,
+            file_ops::rename_image,
+            file_ops::batch_rename,
+            file_ops::move_image,
+            file_ops::undo_last_file_operation,
+            file_ops::delete_images,
+            tags::set_image_tags,
+            tags::get_image_tags,
+            tags::find_images_by_tag,
+            operations::cancel_operation,
+            path_allowlist::allow_path,
+            session_paths::rebase_session_paths,
+            session_paths::detect_session_path_drift,
+            session_paths::diff_folder_against_session,
+            session_paths::resync_session_with_folder,
+            text_metadata::get_text_metadata,
+            tags::set_rating,
+            tags::get_rating,
+            tags::mark_seen,
+            tags::is_seen,
+            set_auto_mark_seen,
+            set_large_image_threshold,
+            set_oversize_limit,
+            set_exit_timeout,
+            set_preview_dir,
+            set_preview_format,
+            get_menu_sections_collapsed,
+            set_menu_section_collapsed,
+            preview::get_preview_cache_size,
+            preview::clear_preview_cache,
+            set_cache_db_path,
+            set_max_concurrent_decodes,
             get_supported_image_types,
+            get_supported_mime_types,
             open_folder_dialog,
             open_image_dialog,
+            open_images_dialog,
             save_session_dialog,
+            save_session_copy_dialog,
+            create_session_from_folder,
             load_session_dialog,
             save_auto_session,
             load_auto_session,
             get_recent_sessions,
+            get_recent_sessions_load_status,
+            list_sessions_in_dir,
+            peek_session_header,
+            import_app_data,
             load_session_from_path,
             refresh_menu,
             set_loaded_session,
             clear_loaded_session,
             update_session_file,
+            rename_session,
+            delete_session,
             set_window_title,
+            set_title_template,
+            set_active_image,
+            open_app_data_dir,
+            get_app_data_path,
+            set_cache_max_entries,
+            set_cache_eviction_target_ratio,
+            flush_metadata_cache,
+            export_cache,
+            import_cache,
+            get_recently_viewed_images,
+            diagnostics::check_cache_integrity,
+            print_image,
+            copy_image_to_clipboard,
+            copy_path_to_clipboard,
+            extract_frame,
+            get_blurhash,
+            diagnostics::get_diagnostics,
+            diagnostics::get_version_info,
             exit_app,
+            begin_exit_save,
+            cancel_exit,
             launch_new_instance,
             load_derivative_session,
-            update_skip_corrupt_menu_state
+            update_skip_corrupt_menu_state,
+            set_always_on_top
         ])
         .setup(|app| {
             // --- Build the application menu ---
             // Get recent sessions from state
             let app_state: State<AppState> = app.state();
+
+            // asset:// URLs start with an empty scope and follow the folders the user opens
+            app_state.allowed_paths.set_asset_scope(app.asset_protocol_scope());
             let recent_sessions = app_state.recent_sessions.lock().unwrap().clone();
 
-            // Build "Recent Saved Sessions" submenu using helper function
-            let recent_menu = build_recent_sessions_submenu(&app.handle(), &recent_sessions)?;
-
-            // "File" submenu with our custom items and the native Close Window
-            let file_menu = SubmenuBuilder::new(app, "File")
-                .text("save_session",  "Save Session")
-                .text("load_session", "Load Session")
-                .item(&recent_menu)
-                .separator()
-                // Keep the platform-native Close Window (Cmd/Ctrl+W etc.)
-                .item(&PredefinedMenuItem::close_window(app, Some("Close Window"))?)
-                .build()?;
+            // "File" submenu with our custom items, Recent Saved Sessions and the native Close Window
+            let file_menu = build_file_menu(app.handle(), &recent_sessions)?;
 
-            // "View" submenu with Toggle Controls and Fullscreen options
-            let view_menu = SubmenuBuilder::new(app, "View")
-                .text("toggle_controls", "Toggle Controls")
-                .text("toggle_fullscreen", "Enter Fullscreen")
-                .check("toggle_skip_corrupt", "Skip Corrupt Images")
-                .build()?;
+            // "View" submenu with Toggle Controls, Fullscreen and window options
+            let view_menu = build_view_menu(app.handle())?;
 
             let app_menu = MenuBuilder::new(app)
                 .items(&[&file_menu, &view_menu]) // add more submenus here if you like
@@ -1302,46 +3676,8 @@ pub fn run() {
             // --- Handle menu clicks ---
             // Dispatch simple events to the frontend. (Or perform Rust logic here)
             app.on_menu_event(move |app_handle, event| {
-                use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
-
-                let event_id = event.id().0.as_str();
-                match event_id {
-                    "save_session" => {
-                        // Frontend can listen to this and call save routine / command
-                        let _ = app_handle.emit("menu-save-session", ());
-                    }
-                    "load_session" => {
-                        let _ = app_handle.emit("menu-load-session", ());
-                    }
-                    "load_auto_session_menu" => {
-                        let _ = app_handle.emit("menu-load-auto-session", ());
-                    }
-                    "toggle_controls" => {
-                        let _ = app_handle.emit("menu-toggle-controls", ());
-                    }
-                    "toggle_fullscreen" => {
-                        let _ = app_handle.emit("menu-toggle-fullscreen", ());
-                    }
-                    "toggle_skip_corrupt" => {
-                        let _ = app_handle.emit("menu-toggle-skip-corrupt", ());
-                    }
-                    "reload_session" => {
-                        let _ = app_handle.emit("menu-reload-session", ());
-                    }
-                    "update_session" => {
-                        let _ = app_handle.emit("menu-update-session", ());
-                    }
-                    id if id.starts_with("load_recent_path_") => {
-                        // Extract and decode the path from menu ID
-                        if let Some(encoded_path) = id.strip_prefix("load_recent_path_") {
-                            if let Ok(decoded_bytes) = URL_SAFE_NO_PAD.decode(encoded_path) {
-                                if let Ok(session_path) = String::from_utf8(decoded_bytes) {
-                                    let _ = app_handle.emit("menu-load-recent-session", session_path);
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
+                if let Some(action) = MenuAction::parse(event.id().0.as_str()) {
+                    action.emit(app_handle);
                 }
             });
 
@@ -1365,11 +3701,45 @@ pub fn run() {
                         // Prevent default close behavior to allow async session save
                         api.prevent_close();
                         // The tauri://close-requested event will be emitted to the frontend
-                        // Frontend will save the session and then call exit_app command
+                        // Frontend will save the session and then call exit_app or cancel_exit
                     }
                 });
             }
 
+            // --- Report cache evictions to the frontend ---
+            let eviction_handle = app.handle().clone();
+            app_state.metadata_cache.set_eviction_listener(Box::new(move |removed, entry_count| {
+                let _ = eviction_handle.emit("cache-evicted", CacheEvictedEvent { removed, entry_count });
+            }));
+
+            // --- Restore the always-on-top preference ---
+            let always_on_top = app_state.settings.lock().unwrap().always_on_top;
+            if always_on_top {
+                if let Err(e) = apply_always_on_top(app.handle(), true) {
+                    eprintln!("Warning: Failed to restore always on top: {}", e);
+                }
+            }
+
+            // --- Reopen the last browsed folder if enabled ---
+            // Skipped quietly when the folder was deleted or is unreachable (e.g. an unmounted share)
+            let settings = app_state.settings.lock().unwrap().clone();
+            if settings.reopen_last_folder {
+                if let Some(last_folder) = settings.last_folder.filter(|folder| Path::new(folder).is_dir()) {
+                    println!("Reopening last folder: {}", last_folder);
+                    let _ = app.emit("open-last-folder", last_folder);
+                }
+            }
+
+            // --- Warm the metadata cache for recently used images ---
+            let settings = app_state.settings.lock().unwrap().clone();
+            if settings.warm_cache_on_startup {
+                cache_warming::spawn_cache_warming(
+                    settings,
+                    app_state.metadata_cache.clone(),
+                    app_state.decode_permits.clone(),
+                );
+            }
+
             // keep your existing logging init
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -1384,3 +3754,140 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    // Create empty files; listing only looks at extensions, so they needn't be valid images
+    fn touch(dir: &Path, names: &[&str]) {
+        for name in names {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+    }
+
+    fn names(entries: &[FileEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    fn entry_path(dir: &Path, name: &str) -> String {
+        dir.join(name).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn list_images_sorts_by_name_in_natural_order() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), &["img10.png", "img2.png", "IMG1.jpg", "notes.txt", ".hidden.png"]);
+        fs::create_dir(dir.path().join("folder.png")).unwrap();
+        let cache = MetadataCache::in_memory(1000).unwrap();
+
+        let entries = list_images(dir.path(), SortMode::Name, &ImageFilter::default(), &cache).unwrap();
+        assert_eq!(names(&entries), ["IMG1.jpg", "img2.png", "img10.png"]);
+    }
+
+    #[test]
+    fn list_images_sorts_by_date_taken_falling_back_to_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for (name, offset) in [("a.png", 300), ("b.png", 100), ("c.png", 200)] {
+            let file = fs::File::create(dir.path().join(name)).unwrap();
+            file.set_modified(base + Duration::from_secs(offset)).unwrap();
+        }
+        let cache = MetadataCache::in_memory(1000).unwrap();
+
+        let entries = list_images(dir.path(), SortMode::DateTaken, &ImageFilter::default(), &cache).unwrap();
+        assert_eq!(names(&entries), ["b.png", "c.png", "a.png"]);
+    }
+
+    #[test]
+    fn list_images_applies_every_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), &["beach1.png", "beach2.png", "city.png", ".beach3.png"]);
+        let cache = MetadataCache::in_memory(1000).unwrap();
+
+        let by_query = ImageFilter { query: Some(" BEACH ".to_string()), ..Default::default() };
+        let entries = list_images(dir.path(), SortMode::Name, &by_query, &cache).unwrap();
+        assert_eq!(names(&entries), ["beach1.png", "beach2.png"]);
+
+        let with_hidden = ImageFilter { query: Some("beach".to_string()), show_hidden: true, ..Default::default() };
+        let entries = list_images(dir.path(), SortMode::Name, &with_hidden, &cache).unwrap();
+        assert_eq!(names(&entries), [".beach3.png", "beach1.png", "beach2.png"]);
+
+        cache.set_rating(&entry_path(dir.path(), "beach2.png"), 4).unwrap();
+        cache.set_rating(&entry_path(dir.path(), "city.png"), 2).unwrap();
+        let rated = ImageFilter { min_rating: Some(3), ..Default::default() };
+        let entries = list_images(dir.path(), SortMode::Name, &rated, &cache).unwrap();
+        assert_eq!(names(&entries), ["beach2.png"]);
+
+        cache.mark_seen(&entry_path(dir.path(), "beach1.png")).unwrap();
+        let unseen = ImageFilter { unseen_only: true, ..Default::default() };
+        let entries = list_images(dir.path(), SortMode::Name, &unseen, &cache).unwrap();
+        assert_eq!(names(&entries), ["beach2.png", "city.png"]);
+    }
+
+    #[test]
+    fn list_images_reports_a_missing_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MetadataCache::in_memory(1000).unwrap();
+
+        let result = list_images(&dir.path().join("missing"), SortMode::Name, &ImageFilter::default(), &cache);
+        assert!(result.unwrap_err().starts_with("Failed to read directory"));
+    }
+
+    fn recent(path: &str) -> RecentSession {
+        RecentSession { path: path.to_string(), last_opened: None }
+    }
+
+    #[test]
+    fn merge_recent_sessions_skips_duplicates_and_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), &["a.session.json", "b.session.json"]);
+        let a = entry_path(dir.path(), "a.session.json");
+        let b = entry_path(dir.path(), "b.session.json");
+        let a_respelled = format!("{}/", a.replace('/', "\\"));
+
+        let mut existing = vec![recent(&a)];
+        let incoming = vec![recent(&a_respelled), recent(&b), recent(&entry_path(dir.path(), "gone.session.json"))];
+        assert_eq!(merge_recent_sessions(&mut existing, incoming), 1);
+        let paths: Vec<&str> = existing.iter().map(|session| session.path.as_str()).collect();
+        assert_eq!(paths, [a.as_str(), b.as_str()]);
+    }
+
+    #[test]
+    fn merge_recent_sessions_stops_at_the_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let names: Vec<String> = (0..MAX_RECENT_SESSIONS + 3).map(|i| format!("{}.session.json", i)).collect();
+        touch(dir.path(), &names.iter().map(String::as_str).collect::<Vec<_>>());
+        let paths: Vec<String> = names.iter().map(|name| entry_path(dir.path(), name)).collect();
+
+        let mut existing = vec![recent(&paths[0]), recent(&paths[1])];
+        let incoming = paths[2..].iter().map(|path| recent(path)).collect();
+        assert_eq!(merge_recent_sessions(&mut existing, incoming), MAX_RECENT_SESSIONS - 2);
+        assert_eq!(existing.len(), MAX_RECENT_SESSIONS);
+        assert_eq!(existing[0].path, paths[0]);
+    }
+
+    #[test]
+    fn merge_session_paths_skips_duplicates_and_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), &["fav.session.json", "new.session.json"]);
+        let favorite = entry_path(dir.path(), "fav.session.json");
+        let new = entry_path(dir.path(), "new.session.json");
+
+        let mut existing = vec![favorite.clone()];
+        let incoming = vec![favorite.clone(), new.clone(), entry_path(dir.path(), "gone.session.json")];
+        assert_eq!(merge_session_paths(&mut existing, incoming), 1);
+        assert_eq!(existing, [favorite, new]);
+    }
+
+    #[test]
+    fn auto_session_import_name_never_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(auto_session_import_name(dir.path(), "auto-session.json"), "auto-session.json");
+
+        touch(dir.path(), &["auto-session.json", "auto-session-work.json", "auto-session-work-imported.json"]);
+        assert_eq!(auto_session_import_name(dir.path(), "auto-session.json"), "auto-session-imported.json");
+        assert_eq!(auto_session_import_name(dir.path(), "auto-session-work.json"), "auto-session-work-imported-2.json");
+    }
+}
@@ -0,0 +1,92 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use tauri::{AppHandle, Emitter};
+
+// Recent session items carry their path in the id, base64-encoded to survive special characters
+const RECENT_SESSION_ID_PREFIX: &str = "load_recent_path_";
+
+/// Every custom menu item. Menus are built from these ids and clicks are parsed back into them,
+/// so an item can't be added on one side and forgotten on the other.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuAction {
+    SaveSession,
+    LoadSession,
+    LoadAutoSession,
+    LoadRecentSession(String),
+    ToggleControls,
+    ToggleFullscreen,
+    ToggleSkipCorrupt,
+    ToggleAlwaysOnTop,
+    CopyPath,
+    ReloadSession,
+    UpdateSession,
+}
+
+impl MenuAction {
+    /// The menu item id for this action
+    pub fn id(&self) -> String {
+        match self {
+            Self::SaveSession => "save_session".to_string(),
+            Self::LoadSession => "load_session".to_string(),
+            Self::LoadAutoSession => "load_auto_session_menu".to_string(),
+            Self::LoadRecentSession(path) => format!("{}{}", RECENT_SESSION_ID_PREFIX, URL_SAFE_NO_PAD.encode(path.as_bytes())),
+            Self::ToggleControls => "toggle_controls".to_string(),
+            Self::ToggleFullscreen => "toggle_fullscreen".to_string(),
+            Self::ToggleSkipCorrupt => "toggle_skip_corrupt".to_string(),
+            Self::ToggleAlwaysOnTop => "toggle_always_on_top".to_string(),
+            Self::CopyPath => "copy_path".to_string(),
+            Self::ReloadSession => "reload_session".to_string(),
+            Self::UpdateSession => "update_session".to_string(),
+        }
+    }
+
+    /// Parse a clicked menu item id; None for predefined items and undecodable recent paths
+    pub fn parse(id: &str) -> Option<Self> {
+        if let Some(encoded_path) = id.strip_prefix(RECENT_SESSION_ID_PREFIX) {
+            let decoded_bytes = URL_SAFE_NO_PAD.decode(encoded_path).ok()?;
+            return String::from_utf8(decoded_bytes).ok().map(Self::LoadRecentSession);
+        }
+
+        match id {
+            "save_session" => Some(Self::SaveSession),
+            "load_session" => Some(Self::LoadSession),
+            "load_auto_session_menu" => Some(Self::LoadAutoSession),
+            "toggle_controls" => Some(Self::ToggleControls),
+            "toggle_fullscreen" => Some(Self::ToggleFullscreen),
+            "toggle_skip_corrupt" => Some(Self::ToggleSkipCorrupt),
+            "toggle_always_on_top" => Some(Self::ToggleAlwaysOnTop),
+            "copy_path" => Some(Self::CopyPath),
+            "reload_session" => Some(Self::ReloadSession),
+            "update_session" => Some(Self::UpdateSession),
+            _ => None,
+        }
+    }
+
+    // Helper function to name the frontend event each action is forwarded as
+    fn event_name(&self) -> &'static str {
+        match self {
+            Self::SaveSession => "menu-save-session",
+            Self::LoadSession => "menu-load-session",
+            Self::LoadAutoSession => "menu-load-auto-session",
+            Self::LoadRecentSession(_) => "menu-load-recent-session",
+            Self::ToggleControls => "menu-toggle-controls",
+            Self::ToggleFullscreen => "menu-toggle-fullscreen",
+            Self::ToggleSkipCorrupt => "menu-toggle-skip-corrupt",
+            Self::ToggleAlwaysOnTop => "menu-toggle-always-on-top",
+            // Frontend knows the current image and calls copy_path_to_clipboard
+            Self::CopyPath => "menu-copy-path",
+            Self::ReloadSession => "menu-reload-session",
+            Self::UpdateSession => "menu-update-session",
+        }
+    }
+
+    /// Forward the action to the frontend, which owns the matching save/load/view routines
+    pub fn emit(&self, app: &AppHandle) {
+        let result = match self {
+            Self::LoadRecentSession(path) => app.emit(self.event_name(), path),
+            _ => app.emit(self.event_name(), ()),
+        };
+        if let Err(e) = result {
+            eprintln!("Warning: Failed to forward menu action {:?}: {}", self, e);
+        }
+    }
+}
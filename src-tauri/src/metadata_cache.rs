@@ -1,8 +1,15 @@
 use rusqlite::{Connection, params, OptionalExtension};
-use std::path::PathBuf;
+use serde::de::{self, Deserializer as _, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use chrono::Utc;
 
+use crate::paths::canonicalize_path;
+
 /// Cached metadata for an image file
 #[derive(Debug, Clone)]
 pub struct CachedMetadata {
@@ -10,28 +17,137 @@ pub struct CachedMetadata {
     pub height: u32,
     #[allow(dead_code)]
     pub file_size: u64,
+    pub format: String,
+    pub has_icc_profile: bool,
+    pub icc_profile_name: Option<String>,
+}
+
+/// One image_metadata row as written by `export_all` and read back by `insert_rows`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheRow {
+    pub file_path: String,
+    pub last_modified: String,
+    pub width: u32,
+    pub height: u32,
+    pub file_size: u64,
+    pub last_accessed: String,
+    // Missing in rows from older exports; such rows are treated as stale on first read
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub has_icc_profile: Option<bool>,
+    #[serde(default)]
+    pub icc_profile_name: Option<String>,
+    #[serde(default)]
+    pub viewed_at: Option<String>,
+}
+
+/// An image_metadata row as listed by `recently_viewed`
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentlyViewed {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub viewed_at: String, // RFC 3339
+}
+
+// Every row is keyed by the canonical path so the same file never gets two entries
+fn cache_key(file_path: &str) -> String {
+    canonicalize_path(Path::new(file_path)).to_string_lossy().to_string()
 }
 
+/// Smallest allowed cache size, so the cache can't be configured into uselessness
+pub const MIN_MAX_ENTRIES: usize = 100;
+
+/// Default fraction of `max_entries` to evict down to once the limit is exceeded
+pub const DEFAULT_EVICTION_TARGET_RATIO: f64 = 0.9;
+
+/// Rows written per transaction by `import_all`
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// Callback invoked after LRU eviction with (entries removed, entries remaining)
+pub type EvictionListener = Box<dyn Fn(usize, usize) + Send + Sync>;
+
 /// SQLite-backed persistent cache for image metadata
 pub struct MetadataCache {
     conn: Arc<Mutex<Connection>>,
-    max_entries: usize,
+    db_path: Option<PathBuf>, // None for an in-memory cache
+    max_entries: AtomicUsize,
+    eviction_target_ratio: Mutex<f64>,
+    eviction_listener: Mutex<Option<EvictionListener>>,
 }
 
 impl MetadataCache {
-    /// Create or open the metadata cache database
+    /// Create or open the metadata cache database in the app data directory
     pub fn new(max_entries: usize) -> Result<Self, String> {
-        let db_path = Self::get_cache_db_path()?;
+        Self::with_path(Self::get_cache_db_path()?, max_entries)
+    }
+
+    /// Create or open the metadata cache database at a specific path
+    pub fn with_path(db_path: PathBuf, max_entries: usize) -> Result<Self, String> {
+        let conn = Self::open_database(&db_path)?;
+        println!("Metadata cache initialized at: {}", db_path.display());
+
+        Ok(Self::from_connection(conn, Some(db_path), max_entries))
+    }
+
+    /// Create an empty cache that lives only in memory and is gone when the app exits.
+    /// Behaves like a file-backed cache in every other way.
+    pub fn in_memory(max_entries: usize) -> Result<Self, String> {
+        let conn = Self::open_in_memory_database()?;
+        println!("Metadata cache initialized in memory");
+
+        Ok(Self::from_connection(conn, None, max_entries))
+    }
 
+    fn from_connection(conn: Connection, db_path: Option<PathBuf>, max_entries: usize) -> Self {
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+            db_path,
+            max_entries: AtomicUsize::new(max_entries),
+            eviction_target_ratio: Mutex::new(DEFAULT_EVICTION_TARGET_RATIO),
+            eviction_listener: Mutex::new(None),
+        }
+    }
+
+    // Open the database file, configure journaling and create or migrate the schema
+    fn open_database(db_path: &Path) -> Result<Connection, String> {
         // Ensure the directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)
                 .map_err(|e| format!("Failed to create cache directory: {}", e))?;
         }
 
-        let conn = Connection::open(&db_path)
+        let conn = Connection::open(db_path)
             .map_err(|e| format!("Failed to open cache database: {}", e))?;
 
+        // Use WAL journaling so `flush` has a log to checkpoint, with NORMAL sync
+        // (durable across app crashes, fast enough for bulk scans)
+        let journal_mode: String = conn
+            .query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to enable WAL mode: {}", e))?;
+        if !journal_mode.eq_ignore_ascii_case("wal") {
+            eprintln!("Warning: Cache database is using '{}' journaling instead of WAL", journal_mode);
+        }
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .map_err(|e| format!("Failed to set synchronous mode: {}", e))?;
+        conn.pragma_update(None, "wal_autocheckpoint", 1000)
+            .map_err(|e| format!("Failed to set WAL autocheckpoint: {}", e))?;
+
+        Self::create_schema(&conn)?;
+        Ok(conn)
+    }
+
+    // Open a private in-memory database with the same schema as the file
+    fn open_in_memory_database() -> Result<Connection, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open in-memory cache database: {}", e))?;
+        Self::create_schema(&conn)?;
+        Ok(conn)
+    }
+
+    // Create or migrate the schema
+    fn create_schema(conn: &Connection) -> Result<(), String> {
         // Initialize the database schema
         conn.execute(
             "CREATE TABLE IF NOT EXISTS image_metadata (
@@ -40,91 +156,178 @@ impl MetadataCache {
                 width INTEGER NOT NULL,
                 height INTEGER NOT NULL,
                 file_size INTEGER NOT NULL,
-                last_accessed TEXT NOT NULL
+                last_accessed TEXT NOT NULL,
+                format TEXT,
+                has_icc_profile INTEGER,
+                icc_profile_name TEXT,
+                viewed_at TEXT
             )",
             [],
         ).map_err(|e| format!("Failed to create table: {}", e))?;
 
+        // Databases created by older versions need the columns added since
+        for (column, column_type) in [("format", "TEXT"), ("has_icc_profile", "INTEGER"), ("icc_profile_name", "TEXT"), ("viewed_at", "TEXT")] {
+            let has_column = conn
+                .prepare("SELECT 1 FROM pragma_table_info('image_metadata') WHERE name = ?1")
+                .and_then(|mut stmt| stmt.exists(params![column]))
+                .map_err(|e| format!("Failed to inspect cache schema: {}", e))?;
+            if !has_column {
+                conn.execute(&format!("ALTER TABLE image_metadata ADD COLUMN {} {}", column, column_type), [])
+                    .map_err(|e| format!("Failed to add {} column: {}", column, e))?;
+            }
+        }
+
         // Create index on last_accessed for efficient LRU eviction
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_last_accessed ON image_metadata(last_accessed)",
             [],
         ).map_err(|e| format!("Failed to create index: {}", e))?;
 
-        println!("Metadata cache initialized at: {}", db_path.display());
+        // Views are stamped separately from last_accessed, which every metadata read bumps
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_viewed_at ON image_metadata(viewed_at)",
+            [],
+        ).map_err(|e| format!("Failed to create viewed index: {}", e))?;
 
-        Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
-            max_entries,
-        })
+        // User tags live alongside the cache but are never evicted or cleared with it
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS image_tags (
+                file_path TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (file_path, tag)
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create tags table: {}", e))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_tag ON image_tags(tag)",
+            [],
+        ).map_err(|e| format!("Failed to create tags index: {}", e))?;
+
+        // Blurhashes are derived data, so they are cleared with the cache
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS image_blurhash (
+                file_path TEXT NOT NULL,
+                last_modified TEXT NOT NULL,
+                components_x INTEGER NOT NULL,
+                components_y INTEGER NOT NULL,
+                hash TEXT NOT NULL,
+                PRIMARY KEY (file_path, components_x, components_y)
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create blurhash table: {}", e))?;
+
+        // EXIF capture dates are derived data like blurhashes; a NULL date records "no EXIF date"
+        // so files without one aren't re-parsed on every sort
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS image_date_taken (
+                file_path TEXT PRIMARY KEY,
+                last_modified TEXT NOT NULL,
+                date_taken TEXT
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create date taken table: {}", e))?;
+
+        // Ratings are user data too, so they get their own table rather than an evictable column
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS image_ratings (
+                file_path TEXT PRIMARY KEY,
+                rating INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create ratings table: {}", e))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS image_seen (
+                file_path TEXT PRIMARY KEY,
+                seen_at TEXT NOT NULL
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create seen table: {}", e))?;
+
+        Ok(())
     }
 
     /// Get the platform-specific path for the cache database
     fn get_cache_db_path() -> Result<PathBuf, String> {
-        let app_data_dir = dirs::data_dir()
-            .ok_or("Failed to get application data directory")?
-            .join("image-viewer");
-        Ok(app_data_dir.join("metadata.db"))
+        Ok(crate::paths::get_app_data_dir()?.join("metadata.db"))
+    }
+
+    /// Register a callback to be notified whenever entries are evicted
+    pub fn set_eviction_listener(&self, listener: EvictionListener) {
+        *self.eviction_listener.lock().unwrap() = Some(listener);
     }
 
     /// Get cached metadata for a file if it exists and is still valid
     pub fn get(&self, file_path: &str, last_modified: &str) -> Result<Option<CachedMetadata>, String> {
+        let file_path = &cache_key(file_path);
         let conn = self.conn.lock().unwrap();
 
-        let result: Option<(u32, u32, u64, String)> = conn
-            .query_row(
-                "SELECT width, height, file_size, last_modified FROM image_metadata WHERE file_path = ?1",
-                params![file_path],
-                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        // Statements are prepared once per connection and reused (these run for every image read)
+        type Row = (u32, u32, u64, String, Option<String>, Option<bool>, Option<String>);
+        let result: Option<Row> = conn
+            .prepare_cached(
+                "SELECT width, height, file_size, last_modified, format, has_icc_profile, icc_profile_name
+                 FROM image_metadata WHERE file_path = ?1",
             )
-            .optional()
+            .and_then(|mut stmt| {
+                stmt.query_row(
+                    params![file_path],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?)),
+                ).optional()
+            })
             .map_err(|e| format!("Cache query failed: {}", e))?;
 
-        if let Some((width, height, file_size, cached_modified)) = result {
-            // Check if the file has been modified since caching
-            if cached_modified == last_modified {
+        if let Some((width, height, file_size, cached_modified, cached_format, cached_icc, icc_profile_name)) = result {
+            // Check if the file has been modified since caching (entries written before
+            // the format/ICC columns existed are treated as stale so they get re-read)
+            if let (true, Some(format), Some(has_icc_profile)) = (cached_modified == last_modified, cached_format, cached_icc) {
                 // Update last_accessed timestamp
                 let now = Utc::now().to_rfc3339();
-                conn.execute(
-                    "UPDATE image_metadata SET last_accessed = ?1 WHERE file_path = ?2",
-                    params![now, file_path],
-                ).map_err(|e| format!("Failed to update last_accessed: {}", e))?;
+                conn.prepare_cached("UPDATE image_metadata SET last_accessed = ?1 WHERE file_path = ?2")
+                    .and_then(|mut stmt| stmt.execute(params![now, file_path]))
+                    .map_err(|e| format!("Failed to update last_accessed: {}", e))?;
 
                 return Ok(Some(CachedMetadata {
                     width,
                     height,
                     file_size,
+                    format,
+                    has_icc_profile,
+                    icc_profile_name,
                 }));
-            } else {
-                // File was modified, remove stale entry
-                conn.execute(
-                    "DELETE FROM image_metadata WHERE file_path = ?1",
-                    params![file_path],
-                ).map_err(|e| format!("Failed to delete stale entry: {}", e))?;
             }
+            // File was modified: report a miss but leave the stale row for `set` to replace,
+            // so the view history it carries survives the re-read
         }
 
         Ok(None)
     }
 
     /// Store metadata in the cache
-    pub fn set(
-        &self,
-        file_path: &str,
-        last_modified: &str,
-        width: u32,
-        height: u32,
-        file_size: u64,
-    ) -> Result<(), String> {
+    pub fn set(&self, file_path: &str, last_modified: &str, metadata: &CachedMetadata) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
         let conn = self.conn.lock().unwrap();
         let now = Utc::now().to_rfc3339();
 
-        // Insert or replace the entry
-        conn.execute(
-            "INSERT OR REPLACE INTO image_metadata (file_path, last_modified, width, height, file_size, last_accessed)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![file_path, last_modified, width, height, file_size, now],
-        ).map_err(|e| format!("Failed to insert cache entry: {}", e))?;
+        // Insert or replace the entry, keeping when it was last viewed
+        conn.prepare_cached(
+            "INSERT OR REPLACE INTO image_metadata
+             (file_path, last_modified, width, height, file_size, last_accessed, format, has_icc_profile, icc_profile_name, viewed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, (SELECT viewed_at FROM image_metadata WHERE file_path = ?1))",
+        )
+            .and_then(|mut stmt| stmt.execute(params![
+                file_path,
+                last_modified,
+                metadata.width,
+                metadata.height,
+                metadata.file_size,
+                now,
+                metadata.format,
+                metadata.has_icc_profile,
+                metadata.icc_profile_name,
+            ]))
+            .map_err(|e| format!("Failed to insert cache entry: {}", e))?;
 
         // Check if we need to evict old entries (LRU)
         self.evict_if_needed(&conn)?;
@@ -135,11 +338,15 @@ impl MetadataCache {
     /// Evict least recently used entries if cache exceeds max size
     fn evict_if_needed(&self, conn: &Connection) -> Result<(), String> {
         let count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM image_metadata", [], |row| row.get(0))
+            .prepare_cached("SELECT COUNT(*) FROM image_metadata")
+            .and_then(|mut stmt| stmt.query_row([], |row| row.get(0)))
             .map_err(|e| format!("Failed to count entries: {}", e))?;
 
-        if count as usize > self.max_entries {
-            let to_delete = count as usize - self.max_entries;
+        // Evict below the limit (to the low watermark) so a bulk scan doesn't delete a few rows on every insert
+        let max_entries = self.max_entries.load(Ordering::Relaxed);
+        if count as usize > max_entries {
+            let target = (max_entries as f64 * *self.eviction_target_ratio.lock().unwrap()) as usize;
+            let to_delete = count as usize - target;
 
             conn.execute(
                 "DELETE FROM image_metadata WHERE file_path IN (
@@ -148,12 +355,288 @@ impl MetadataCache {
                 params![to_delete],
             ).map_err(|e| format!("Failed to evict entries: {}", e))?;
 
-            println!("Evicted {} old cache entries (LRU)", to_delete);
+            println!(
+                "Evicted {} old cache entries (LRU): {} -> {} entries (limit {}, target {})",
+                to_delete, count, count as usize - to_delete, max_entries, target,
+            );
+
+            if let Some(listener) = self.eviction_listener.lock().unwrap().as_ref() {
+                listener(to_delete, count as usize - to_delete);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Move a cached entry to a new path after the file was renamed or moved
+    pub fn rename(&self, old_path: &str, new_path: &str) -> Result<(), String> {
+        let old_path = &cache_key(old_path);
+        let new_path = &cache_key(new_path);
+        let conn = self.conn.lock().unwrap();
+
+        // OR REPLACE drops any stale row already stored under the new path
+        conn.execute(
+            "UPDATE OR REPLACE image_metadata SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        ).map_err(|e| format!("Failed to rename cache entry: {}", e))?;
+
+        conn.execute(
+            "UPDATE OR REPLACE image_tags SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        ).map_err(|e| format!("Failed to rename image tags: {}", e))?;
+
+        conn.execute(
+            "UPDATE OR REPLACE image_blurhash SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        ).map_err(|e| format!("Failed to rename blurhash entries: {}", e))?;
+
+        conn.execute(
+            "UPDATE OR REPLACE image_date_taken SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        ).map_err(|e| format!("Failed to rename date taken entry: {}", e))?;
+
+        conn.execute(
+            "UPDATE OR REPLACE image_ratings SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        ).map_err(|e| format!("Failed to rename image rating: {}", e))?;
+
+        conn.execute(
+            "UPDATE OR REPLACE image_seen SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        ).map_err(|e| format!("Failed to rename seen flag: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Drop the cached entry for a file that no longer exists
+    pub fn remove(&self, file_path: &str) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare_cached("DELETE FROM image_metadata WHERE file_path = ?1")
+            .and_then(|mut stmt| stmt.execute(params![file_path]))
+            .map_err(|e| format!("Failed to remove cache entry: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get a cached blurhash if one was computed for this version of the file
+    pub fn get_blurhash(&self, file_path: &str, last_modified: &str, components_x: u32, components_y: u32) -> Result<Option<String>, String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare_cached(
+            "SELECT hash FROM image_blurhash
+             WHERE file_path = ?1 AND last_modified = ?2 AND components_x = ?3 AND components_y = ?4",
+        )
+            .and_then(|mut stmt| {
+                stmt.query_row(params![file_path, last_modified, components_x, components_y], |row| row.get(0))
+                    .optional()
+            })
+            .map_err(|e| format!("Blurhash query failed: {}", e))
+    }
+
+    /// Store a blurhash, replacing any computed for an older version of the file
+    pub fn set_blurhash(&self, file_path: &str, last_modified: &str, components_x: u32, components_y: u32, hash: &str) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare_cached(
+            "INSERT OR REPLACE INTO image_blurhash (file_path, last_modified, components_x, components_y, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+            .and_then(|mut stmt| stmt.execute(params![file_path, last_modified, components_x, components_y, hash]))
+            .map_err(|e| format!("Failed to insert blurhash: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get the cached EXIF capture date for this version of a file.
+    /// `Some(None)` means the file was checked and has no date; `None` means it wasn't checked yet.
+    pub fn get_date_taken(&self, file_path: &str, last_modified: &str) -> Result<Option<Option<String>>, String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare_cached("SELECT date_taken FROM image_date_taken WHERE file_path = ?1 AND last_modified = ?2")
+            .and_then(|mut stmt| stmt.query_row(params![file_path, last_modified], |row| row.get(0)).optional())
+            .map_err(|e| format!("Date taken query failed: {}", e))
+    }
+
+    /// Store the EXIF capture date (or its absence), replacing any stored for an older version of the file
+    pub fn set_date_taken(&self, file_path: &str, last_modified: &str, date_taken: Option<&str>) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare_cached(
+            "INSERT OR REPLACE INTO image_date_taken (file_path, last_modified, date_taken) VALUES (?1, ?2, ?3)",
+        )
+            .and_then(|mut stmt| stmt.execute(params![file_path, last_modified, date_taken]))
+            .map_err(|e| format!("Failed to insert date taken: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get the tags attached to a file, sorted alphabetically
+    pub fn get_tags(&self, file_path: &str) -> Result<Vec<String>, String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached("SELECT tag FROM image_tags WHERE file_path = ?1 ORDER BY tag")
+            .map_err(|e| format!("Tag query failed: {}", e))?;
+        let tags = stmt.query_map(params![file_path], |row| row.get(0))
+            .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+            .map_err(|e| format!("Tag query failed: {}", e))?;
+
+        Ok(tags)
+    }
+
+    /// Replace all tags attached to a file
+    pub fn set_tags(&self, file_path: &str, tags: &[String]) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        tx.execute("DELETE FROM image_tags WHERE file_path = ?1", params![file_path])
+            .map_err(|e| format!("Failed to clear image tags: {}", e))?;
+        for tag in tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO image_tags (file_path, tag) VALUES (?1, ?2)",
+                params![file_path, tag],
+            ).map_err(|e| format!("Failed to insert image tag: {}", e))?;
         }
+        tx.commit()
+            .map_err(|e| format!("Failed to save image tags: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Find files under a path prefix that carry the given tag
+    pub fn find_by_tag(&self, path_prefix: &str, tag: &str) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock().unwrap();
+
+        // substr comparison instead of LIKE so '%' and '_' in paths aren't treated as wildcards
+        let mut stmt = conn.prepare_cached(
+            "SELECT file_path FROM image_tags
+             WHERE tag = ?1 AND substr(file_path, 1, length(?2)) = ?2
+             ORDER BY file_path",
+        ).map_err(|e| format!("Tag query failed: {}", e))?;
+        let paths = stmt.query_map(params![tag, path_prefix], |row| row.get(0))
+            .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+            .map_err(|e| format!("Tag query failed: {}", e))?;
+
+        Ok(paths)
+    }
+
+    /// Record that a file was opened for viewing. Does nothing if the file has no cached row.
+    pub fn mark_viewed(&self, file_path: &str) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.prepare_cached("UPDATE image_metadata SET viewed_at = ?1 WHERE file_path = ?2")
+            .and_then(|mut stmt| stmt.execute(params![now, file_path]))
+            .map_err(|e| format!("Failed to mark image as viewed: {}", e))?;
 
         Ok(())
     }
 
+    /// List cached images by when they were last viewed, most recent first; never-viewed rows are left out.
+    /// Rows aren't checked against the disk; files may have moved since.
+    pub fn recently_viewed(&self, limit: usize, offset: usize) -> Result<Vec<RecentlyViewed>, String> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT file_path, width, height, viewed_at FROM image_metadata
+             WHERE viewed_at IS NOT NULL
+             ORDER BY viewed_at DESC LIMIT ?1 OFFSET ?2",
+        ).map_err(|e| format!("Recent images query failed: {}", e))?;
+        let rows = stmt.query_map(params![limit as i64, offset as i64], |row| Ok(RecentlyViewed {
+            path: row.get(0)?,
+            width: row.get(1)?,
+            height: row.get(2)?,
+            viewed_at: row.get(3)?,
+        }))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Recent images query failed: {}", e))?;
+
+        Ok(rows)
+    }
+
+    /// Get the star rating of a file (0 when unrated)
+    pub fn get_rating(&self, file_path: &str) -> Result<u8, String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        let rating: Option<u8> = conn
+            .prepare_cached("SELECT rating FROM image_ratings WHERE file_path = ?1")
+            .and_then(|mut stmt| stmt.query_row(params![file_path], |row| row.get(0)).optional())
+            .map_err(|e| format!("Rating query failed: {}", e))?;
+
+        Ok(rating.unwrap_or(0))
+    }
+
+    /// Set the star rating of a file; a rating of 0 removes the row
+    pub fn set_rating(&self, file_path: &str, rating: u8) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        if rating == 0 {
+            conn.execute("DELETE FROM image_ratings WHERE file_path = ?1", params![file_path])
+        } else {
+            conn.execute(
+                "INSERT OR REPLACE INTO image_ratings (file_path, rating) VALUES (?1, ?2)",
+                params![file_path, rating],
+            )
+        }.map_err(|e| format!("Failed to save image rating: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Whether a file has been marked as seen
+    pub fn is_seen(&self, file_path: &str) -> Result<bool, String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare_cached("SELECT 1 FROM image_seen WHERE file_path = ?1")
+            .and_then(|mut stmt| stmt.exists(params![file_path]))
+            .map_err(|e| format!("Seen query failed: {}", e))
+    }
+
+    /// Mark a file as seen, keeping the time it was first seen
+    pub fn mark_seen(&self, file_path: &str) -> Result<(), String> {
+        let file_path = &cache_key(file_path);
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.prepare_cached("INSERT OR IGNORE INTO image_seen (file_path, seen_at) VALUES (?1, ?2)")
+            .and_then(|mut stmt| stmt.execute(params![file_path, now]))
+            .map_err(|e| format!("Failed to mark image as seen: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Change the maximum number of entries, evicting immediately if the cache is now over the limit
+    pub fn set_max_entries(&self, max_entries: usize) -> Result<(), String> {
+        if max_entries < MIN_MAX_ENTRIES {
+            return Err(format!("Cache size must be at least {} entries", MIN_MAX_ENTRIES));
+        }
+
+        let conn = self.conn.lock().unwrap();
+        self.max_entries.store(max_entries, Ordering::Relaxed);
+        self.evict_if_needed(&conn)
+    }
+
+    /// Change how far below `max_entries` eviction goes, as a fraction of the limit (0.5 to 1.0).
+    /// 1.0 evicts only the overflow, as before the low watermark existed.
+    pub fn set_eviction_target_ratio(&self, ratio: f64) -> Result<(), String> {
+        if !(0.5..=1.0).contains(&ratio) {
+            return Err(format!("Eviction target ratio must be between 0.5 and 1.0, got {}", ratio));
+        }
+        *self.eviction_target_ratio.lock().unwrap() = ratio;
+        Ok(())
+    }
+
     /// Get cache statistics
     pub fn get_stats(&self) -> Result<CacheStats, String> {
         let conn = self.conn.lock().unwrap();
@@ -164,7 +647,7 @@ impl MetadataCache {
 
         Ok(CacheStats {
             entry_count: count as usize,
-            max_entries: self.max_entries,
+            max_entries: self.max_entries.load(Ordering::Relaxed),
         })
     }
 
@@ -174,12 +657,176 @@ impl MetadataCache {
         let conn = self.conn.lock().unwrap();
         conn.execute("DELETE FROM image_metadata", [])
             .map_err(|e| format!("Failed to clear cache: {}", e))?;
+        conn.execute("DELETE FROM image_blurhash", [])
+            .map_err(|e| format!("Failed to clear blurhash cache: {}", e))?;
+        conn.execute("DELETE FROM image_date_taken", [])
+            .map_err(|e| format!("Failed to clear date taken cache: {}", e))?;
         println!("Cache cleared");
         Ok(())
     }
 
+    /// Run SQLite's integrity check; returns "ok" or the problems found, one per line
+    pub fn integrity_check(&self) -> Result<String, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("PRAGMA integrity_check")
+            .map_err(|e| format!("Failed to prepare integrity check: {}", e))?;
+        let problems = stmt.query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to run integrity check: {}", e))?;
+        Ok(problems.join("\n"))
+    }
+
+    /// Delete the database file and start over with an empty schema.
+    /// Tags, ratings and seen flags live in the same file and are lost too.
+    pub fn recreate(&self) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let Some(db_path) = &self.db_path else {
+            // Dropping the old connection is all it takes to discard an in-memory database
+            *conn = Self::open_in_memory_database()?;
+            println!("Metadata cache recreated in memory");
+            return Ok(());
+        };
+
+        // Swap in a throwaway connection so the old one is closed before its files are removed
+        let placeholder = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open temporary database: {}", e))?;
+        std::mem::replace(&mut *conn, placeholder)
+            .close()
+            .map_err(|(_, e)| format!("Failed to close cache database: {}", e))?;
+
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = db_path.clone().into_os_string();
+            file.push(suffix);
+            let file = PathBuf::from(file);
+            if file.exists() {
+                std::fs::remove_file(&file)
+                    .map_err(|e| format!("Failed to delete {}: {}", file.display(), e))?;
+            }
+        }
+
+        *conn = Self::open_database(db_path)?;
+        println!("Metadata cache recreated at: {}", db_path.display());
+        Ok(())
+    }
+
+    /// Write every image_metadata row to `writer` as a JSON array, one row at a time, returning the row count.
+    /// Tags, ratings and the other tables aren't included.
+    pub fn export_all(&self, writer: &mut impl Write) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT file_path, last_modified, width, height, file_size, last_accessed, format, has_icc_profile, icc_profile_name, viewed_at
+             FROM image_metadata ORDER BY file_path",
+        ).map_err(|e| format!("Failed to prepare cache export: {}", e))?;
+        let rows = stmt.query_map([], |row| Ok(CacheRow {
+            file_path: row.get(0)?,
+            last_modified: row.get(1)?,
+            width: row.get(2)?,
+            height: row.get(3)?,
+            file_size: row.get(4)?,
+            last_accessed: row.get(5)?,
+            format: row.get(6)?,
+            has_icc_profile: row.get(7)?,
+            icc_profile_name: row.get(8)?,
+            viewed_at: row.get(9)?,
+        })).map_err(|e| format!("Failed to read cache rows: {}", e))?;
+
+        let write_error = |e: std::io::Error| format!("Failed to write cache export: {}", e);
+        writer.write_all(b"[").map_err(write_error)?;
+        let mut count = 0;
+        for row in rows {
+            let row = row.map_err(|e| format!("Failed to read cache row: {}", e))?;
+            writer.write_all(if count == 0 { b"\n" } else { b",\n" }).map_err(write_error)?;
+            serde_json::to_writer(&mut *writer, &row)
+                .map_err(|e| format!("Failed to serialize cache row: {}", e))?;
+            count += 1;
+        }
+        writer.write_all(b"\n]\n").map_err(write_error)?;
+
+        Ok(count)
+    }
+
+    /// Insert or replace many rows in one transaction, then evict down to the size limit.
+    /// Returns the number of rows written.
+    pub fn insert_rows(&self, rows: &[CacheRow]) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO image_metadata
+                 (file_path, last_modified, width, height, file_size, last_accessed, format, has_icc_profile, icc_profile_name, viewed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            ).map_err(|e| format!("Failed to prepare cache insert: {}", e))?;
+            for row in rows {
+                stmt.execute(params![
+                    cache_key(&row.file_path),
+                    row.last_modified,
+                    row.width,
+                    row.height,
+                    row.file_size,
+                    row.last_accessed,
+                    row.format,
+                    row.has_icc_profile,
+                    row.icc_profile_name,
+                    row.viewed_at,
+                ]).map_err(|e| format!("Failed to insert cache entry: {}", e))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to save cache entries: {}", e))?;
+
+        self.evict_if_needed(&conn)?;
+        Ok(rows.len())
+    }
+
+    /// Read a JSON array written by `export_all`, inserting rows in batches of IMPORT_BATCH_SIZE as
+    /// they're parsed, returning the row count. Only one batch is held in memory at a time. If the
+    /// input turns out to be malformed, the batches inserted before the error are kept.
+    pub fn import_all(&self, reader: impl Read) -> Result<usize, String> {
+        struct RowsVisitor<'a>(&'a MetadataCache);
+
+        impl<'de> Visitor<'de> for RowsVisitor<'_> {
+            type Value = usize;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an array of cache rows")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<usize, A::Error> {
+                let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+                let mut count = 0;
+                while let Some(row) = seq.next_element::<CacheRow>()? {
+                    batch.push(row);
+                    if batch.len() == IMPORT_BATCH_SIZE {
+                        count += self.0.insert_rows(&batch).map_err(de::Error::custom)?;
+                        batch.clear();
+                    }
+                }
+                if !batch.is_empty() {
+                    count += self.0.insert_rows(&batch).map_err(de::Error::custom)?;
+                }
+                Ok(count)
+            }
+        }
+
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let count = deserializer.deserialize_seq(RowsVisitor(self))
+            .map_err(|e| format!("Failed to import cache export: {}", e))?;
+        deserializer.end()
+            .map_err(|e| format!("Failed to import cache export: {}", e))?;
+
+        Ok(count)
+    }
+
     /// Flush the cache to ensure all data is written to disk
     pub fn flush(&self) -> Result<(), String> {
+        // An in-memory cache has no disk to flush to
+        if self.db_path.is_none() {
+            return Ok(());
+        }
+
         let conn = self.conn.lock().unwrap();
 
         // Execute a checkpoint to flush WAL (Write-Ahead Logging) to the main database file
@@ -197,3 +844,156 @@ pub struct CacheStats {
     pub entry_count: usize,
     pub max_entries: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODIFIED: &str = "2024-01-01 00:00:00 UTC";
+
+    fn sample_metadata(width: u32, height: u32) -> CachedMetadata {
+        CachedMetadata {
+            width,
+            height,
+            file_size: 1024,
+            format: "PNG".to_string(),
+            has_icc_profile: false,
+            icc_profile_name: None,
+        }
+    }
+
+    #[test]
+    fn rename_moves_the_row_to_the_new_path() {
+        let cache = MetadataCache::in_memory(1000).unwrap();
+        let old_path = "/nonexistent-test-dir/before.png";
+        let new_path = "/nonexistent-test-dir/after.png";
+
+        cache.set(old_path, MODIFIED, &sample_metadata(640, 480)).unwrap();
+        cache.set_rating(old_path, 4).unwrap();
+        cache.rename(old_path, new_path).unwrap();
+
+        let moved = cache.get(new_path, MODIFIED).unwrap().expect("row should follow the rename");
+        assert_eq!((moved.width, moved.height), (640, 480));
+        assert_eq!(cache.get_rating(new_path).unwrap(), 4);
+
+        assert!(cache.get(old_path, MODIFIED).unwrap().is_none());
+        assert_eq!(cache.get_rating(old_path).unwrap(), 0);
+        assert_eq!(cache.get_stats().unwrap().entry_count, 1);
+    }
+
+    #[test]
+    fn rename_replaces_a_stale_row_at_the_destination() {
+        let cache = MetadataCache::in_memory(1000).unwrap();
+        let old_path = "/nonexistent-test-dir/new.png";
+        let new_path = "/nonexistent-test-dir/existing.png";
+
+        cache.set(old_path, MODIFIED, &sample_metadata(100, 100)).unwrap();
+        cache.set(new_path, MODIFIED, &sample_metadata(1, 1)).unwrap();
+        cache.rename(old_path, new_path).unwrap();
+
+        let moved = cache.get(new_path, MODIFIED).unwrap().unwrap();
+        assert_eq!((moved.width, moved.height), (100, 100));
+        assert_eq!(cache.get_stats().unwrap().entry_count, 1);
+    }
+
+    #[test]
+    fn many_sets_read_back_through_cached_statements() {
+        let cache = MetadataCache::in_memory(10_000).unwrap();
+        let path = |i: u32| format!("/nonexistent-test-dir/image-{}.png", i);
+
+        for i in 0..3000 {
+            cache.set(&path(i), MODIFIED, &sample_metadata(i, i + 1)).unwrap();
+        }
+
+        for i in 0..3000 {
+            let cached = cache.get(&path(i), MODIFIED).unwrap().expect("every row should be cached");
+            assert_eq!((cached.width, cached.height), (i, i + 1));
+        }
+        assert_eq!(cache.get_stats().unwrap().entry_count, 3000);
+    }
+
+    #[test]
+    fn spelling_variations_share_one_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("photo.png");
+        std::fs::write(&file, b"").unwrap();
+        let plain = file.to_string_lossy().to_string();
+        let dotted = dir.path().join(".").join("photo.png").to_string_lossy().to_string();
+
+        let cache = MetadataCache::in_memory(1000).unwrap();
+        cache.set(&dotted, MODIFIED, &sample_metadata(10, 20)).unwrap();
+        assert!(cache.get(&plain, MODIFIED).unwrap().is_some());
+        assert_eq!(cache.get_stats().unwrap().entry_count, 1);
+
+        // Once the file is gone its key comes from the parent folder, so removal still finds the row
+        std::fs::remove_file(&file).unwrap();
+        cache.remove(&dotted).unwrap();
+        assert_eq!(cache.get_stats().unwrap().entry_count, 0);
+    }
+
+    #[test]
+    fn import_all_reads_back_an_export_in_batches() {
+        let source = MetadataCache::in_memory(10_000).unwrap();
+        let row_count = IMPORT_BATCH_SIZE as u32 * 2 + 7;
+        for i in 0..row_count {
+            source.set(&format!("/nonexistent-test-dir/{}.png", i), MODIFIED, &sample_metadata(i, 1)).unwrap();
+        }
+        let mut export = Vec::new();
+        assert_eq!(source.export_all(&mut export).unwrap(), row_count as usize);
+
+        let target = MetadataCache::in_memory(10_000).unwrap();
+        assert_eq!(target.import_all(export.as_slice()).unwrap(), row_count as usize);
+        let cached = target.get("/nonexistent-test-dir/42.png", MODIFIED).unwrap().unwrap();
+        assert_eq!(cached.width, 42);
+        assert_eq!(target.get_stats().unwrap().entry_count, row_count as usize);
+    }
+
+    #[test]
+    fn import_all_rejects_input_that_is_not_an_array() {
+        let cache = MetadataCache::in_memory(1000).unwrap();
+        assert!(cache.import_all(&b"{}"[..]).is_err());
+        assert!(cache.import_all(&b"[] trailing"[..]).is_err());
+    }
+
+    #[test]
+    fn in_memory_cache_flushes_as_a_no_op_and_recreates_empty() {
+        let cache = MetadataCache::in_memory(1000).unwrap();
+        let path = "/nonexistent-test-dir/photo.png";
+        cache.set(path, MODIFIED, &sample_metadata(10, 20)).unwrap();
+        cache.mark_seen(path).unwrap();
+
+        cache.flush().unwrap();
+        assert!(cache.get(path, MODIFIED).unwrap().is_some());
+
+        cache.recreate().unwrap();
+        assert!(cache.get(path, MODIFIED).unwrap().is_none());
+        assert!(!cache.is_seen(path).unwrap());
+        assert_eq!(cache.get_stats().unwrap().entry_count, 0);
+    }
+
+    #[test]
+    fn recently_viewed_lists_only_marked_views() {
+        let cache = MetadataCache::in_memory(1000).unwrap();
+        let viewed = "/nonexistent-test-dir/viewed.png";
+        let read_only = "/nonexistent-test-dir/read.png";
+        cache.set(viewed, MODIFIED, &sample_metadata(10, 20)).unwrap();
+        cache.set(read_only, MODIFIED, &sample_metadata(30, 40)).unwrap();
+
+        cache.mark_viewed(viewed).unwrap();
+        // A plain metadata read bumps last_accessed but isn't a view
+        cache.get(read_only, MODIFIED).unwrap();
+
+        let recent = cache.recently_viewed(10, 0).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].path, cache_key(viewed));
+        assert_eq!((recent[0].width, recent[0].height), (10, 20));
+
+        // Re-measuring a changed file keeps its view, going through the miss first as reads do
+        let edited = "2024-02-01 00:00:00 UTC";
+        assert!(cache.get(viewed, edited).unwrap().is_none());
+        cache.set(viewed, edited, &sample_metadata(11, 21)).unwrap();
+        let recent = cache.recently_viewed(10, 0).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!((recent[0].width, recent[0].height), (11, 21));
+    }
+}
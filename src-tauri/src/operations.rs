@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::AppState;
+
+/// Cancellation flags for long-running commands, keyed by a caller-chosen operation id
+pub struct OperationRegistry {
+    flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Handle held by a running operation; unregisters its id when dropped
+pub struct OperationGuard {
+    registry: Arc<OperationRegistry>,
+    id: Option<String>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl OperationRegistry {
+    pub fn new() -> Self {
+        Self {
+            flags: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Register an operation. Without an id the operation runs untracked and can't be cancelled.
+    pub fn start(self: &Arc<Self>, id: Option<String>) -> OperationGuard {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Some(id) = &id {
+            self.flags.lock().unwrap().insert(id.clone(), cancelled.clone());
+        }
+        OperationGuard {
+            registry: self.clone(),
+            id,
+            cancelled,
+        }
+    }
+
+    /// Request cancellation; returns false if no operation with this id is running
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.flags.lock().unwrap().get(id) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl OperationGuard {
+    /// Returns a `Cancelled` error once cancellation has been requested; call between items
+    pub fn check(&self) -> Result<(), String> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(format!("Cancelled: operation {} was cancelled", self.id.as_deref().unwrap_or("")));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            let mut flags = self.registry.flags.lock().unwrap();
+            // Only remove our own flag, in case the id was reused by a newer operation
+            if flags.get(id).is_some_and(|flag| Arc::ptr_eq(flag, &self.cancelled)) {
+                flags.remove(id);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_operation(operation_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let cancelled = state.operations.cancel(&operation_id);
+    if cancelled {
+        println!("Cancellation requested for operation: {}", operation_id);
+    }
+    Ok(cancelled)
+}
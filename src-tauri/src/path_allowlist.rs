@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::scope::fs::Scope;
+use tauri::State;
+
+use crate::paths::canonicalize_path;
+use crate::AppState;
+
+/// Directories the user has opened this run; image reads and asset:// URLs are limited to files beneath them
+pub struct PathAllowlist {
+    roots: Mutex<Vec<PathBuf>>,
+    // The asset protocol's scope, set once the app is built; every root is mirrored into it
+    asset_scope: Mutex<Option<Scope>>,
+}
+
+impl PathAllowlist {
+    pub fn new() -> Self {
+        Self {
+            roots: Mutex::new(Vec::new()),
+            asset_scope: Mutex::new(None),
+        }
+    }
+
+    /// Mirror allowed roots into the asset protocol's scope, including any allowed before now
+    pub fn set_asset_scope(&self, scope: Scope) {
+        for root in self.roots.lock().unwrap().iter() {
+            allow_asset_directory(&scope, root);
+        }
+        *self.asset_scope.lock().unwrap() = Some(scope);
+    }
+
+    /// Allow a directory and everything under it. A file allows its parent directory.
+    pub fn allow(&self, path: &Path) {
+        let dir = if path.is_file() {
+            match path.parent() {
+                Some(parent) => parent,
+                None => return,
+            }
+        } else {
+            path
+        };
+
+        let root = canonicalize_path(dir);
+        {
+            let mut roots = self.roots.lock().unwrap();
+            if roots.contains(&root) {
+                return;
+            }
+            roots.push(root.clone());
+        }
+
+        if let Some(scope) = self.asset_scope.lock().unwrap().as_ref() {
+            allow_asset_directory(scope, &root);
+        }
+    }
+
+    pub fn is_allowed(&self, path: &Path) -> bool {
+        let path = canonicalize_path(path);
+        self.roots.lock().unwrap().iter().any(|root| path.starts_with(root))
+    }
+}
+
+// Helper function to let asset:// URLs serve files under a directory
+fn allow_asset_directory(scope: &Scope, dir: &Path) {
+    if let Err(e) = scope.allow_directory(dir, true) {
+        eprintln!("Warning: Failed to allow asset access to {}: {}", dir.display(), e);
+    }
+}
+
+// Helper function to tell whether a path is a filesystem root such as `/`, `C:\` or a UNC share
+fn is_filesystem_root(path: &Path) -> bool {
+    canonicalize_path(path).parent().is_none()
+}
+
+// Helper function to reject reads outside the allowed roots with a `Forbidden` error
+pub fn is_path_allowed(state: &AppState, path: &str) -> Result<(), String> {
+    if state.allowed_paths.is_allowed(Path::new(path)) {
+        Ok(())
+    } else {
+        Err(format!("Forbidden: {} is outside the folders opened in this session", path))
+    }
+}
+
+#[tauri::command]
+pub async fn allow_path(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    let path_obj = Path::new(&path);
+    if !path_obj.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    if is_filesystem_root(path_obj) {
+        return Err(format!("Forbidden: refusing to allow a whole filesystem root: {}", path));
+    }
+
+    state.allowed_paths.allow(path_obj);
+    println!("Allowed image reads under: {}", path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allow_covers_the_folder_and_everything_under_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("nested");
+        std::fs::create_dir(&nested).unwrap();
+        std::fs::write(nested.join("photo.png"), b"").unwrap();
+        let allowlist = PathAllowlist::new();
+
+        allowlist.allow(&nested.join("photo.png"));
+        assert!(allowlist.is_allowed(&nested.join("other.png")));
+        assert!(!allowlist.is_allowed(&dir.path().join("outside.png")));
+
+        allowlist.allow(dir.path());
+        assert!(allowlist.is_allowed(&dir.path().join("outside.png")));
+    }
+
+    #[test]
+    fn filesystem_roots_are_recognised() {
+        assert!(is_filesystem_root(Path::new("/")));
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!is_filesystem_root(dir.path()));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn drive_roots_are_recognised() {
+        assert!(is_filesystem_root(Path::new("C:\\")));
+    }
+}
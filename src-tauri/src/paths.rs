@@ -0,0 +1,350 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Maximum length (in bytes) of a sanitized filename component, leaving room for suffixes
+/// such as `.session.json` within the common 255-byte filesystem limit
+const MAX_FILENAME_BYTES: usize = 200;
+
+/// Names Windows reserves for devices, regardless of extension
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turn user-provided text into a safe filename component.
+///
+/// The Windows rules are applied on every platform since session files are
+/// often shared between machines: illegal characters are replaced with `_`,
+/// trailing dots/spaces are trimmed, reserved device names are prefixed and
+/// the result is capped in length. Returns an empty string if nothing usable remains.
+pub fn sanitize_filename(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let mut sanitized = replaced
+        .trim()
+        .trim_end_matches(['.', ' '])
+        .to_string();
+
+    // Truncate on a char boundary
+    if sanitized.len() > MAX_FILENAME_BYTES {
+        let mut end = MAX_FILENAME_BYTES;
+        while !sanitized.is_char_boundary(end) {
+            end -= 1;
+        }
+        sanitized.truncate(end);
+        sanitized = sanitized.trim_end_matches(['.', ' ']).to_string();
+    }
+
+    let stem = sanitized.split('.').next().unwrap_or("");
+    if WINDOWS_RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+/// Get the application data directory: `<data dir>/image-viewer`, falling back to
+/// `<config dir>/image-viewer` and then `image-viewer-data` next to the executable for
+/// sandboxed or headless setups without a data directory. Resolved once per run; the choice is logged.
+pub fn get_app_data_dir() -> Result<PathBuf, String> {
+    static APP_DATA_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    APP_DATA_DIR
+        .get_or_init(|| {
+            let resolved = if let Some(dir) = dirs::data_dir() {
+                Some((dir.join("image-viewer"), "data directory"))
+            } else if let Some(dir) = dirs::config_dir() {
+                Some((dir.join("image-viewer"), "config directory (no data directory available)"))
+            } else {
+                std::env::current_exe()
+                    .ok()
+                    .and_then(|exe| exe.parent().map(|dir| dir.join("image-viewer-data")))
+                    .map(|dir| (dir, "executable directory (no data or config directory available)"))
+            };
+
+            match resolved {
+                Some((dir, source)) => {
+                    println!("Using app data directory {} from the {}", dir.display(), source);
+                    Some(dir)
+                }
+                None => {
+                    eprintln!("Failed to find any usable application data directory");
+                    None
+                }
+            }
+        })
+        .clone()
+        .ok_or_else(|| "Failed to get application data directory".to_string())
+}
+
+/// Resolve a path to its canonical form (without the `\\?\` prefix on Windows).
+/// Paths that don't exist (e.g. a file that was just moved away) are resolved through
+/// their parent directory, and left as-is if that fails too.
+pub fn canonicalize_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = dunce::canonicalize(path) {
+        return canonical;
+    }
+
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => dunce::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Rewrite a path into a form that compares equal across spelling variations, for deduplication:
+/// separators become `/`, a drive letter is lowercased and trailing separators are dropped
+/// (except on a bare root such as `/` or `c:/`). The path doesn't need to exist and nothing is
+/// resolved, so `..` and symlinks are left alone. Drive letters are handled on every platform
+/// since session files are shared between machines.
+pub fn normalize_path(path: &str) -> String {
+    let mut normalized = path.replace('\\', "/");
+
+    let bytes = normalized.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        normalized[..1].make_ascii_lowercase();
+    }
+
+    let trimmed_len = normalized.trim_end_matches('/').len();
+    let is_root = trimmed_len == 0 || (trimmed_len == 2 && normalized.as_bytes()[1] == b':');
+    if trimmed_len < normalized.len() {
+        normalized.truncate(if is_root { trimmed_len + 1 } else { trimmed_len });
+    }
+
+    normalized
+}
+
+/// Whether two paths name the same file once spelling differences are normalized away
+pub fn same_path(a: &str, b: &str) -> bool {
+    normalize_path(a) == normalize_path(b)
+}
+
+/// Windows' classic MAX_PATH limit, counting the terminating NUL
+#[cfg(target_os = "windows")]
+const WINDOWS_MAX_PATH: usize = 260;
+
+/// Prepare a path for filesystem calls that may exceed the platform path limit.
+///
+/// On Windows, absolute paths of MAX_PATH - 1 (259) characters or more get the verbatim `\\?\`
+/// prefix (`\\?\UNC\` for network shares), which lifts the limit to ~32k characters. Shorter
+/// paths are returned unchanged so error messages and logs keep their familiar form.
+/// Everywhere else this is a no-op; the OS limits there are far larger.
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let raw = path.to_string_lossy();
+        if path.is_absolute() && raw.len() >= WINDOWS_MAX_PATH - 1 && !raw.starts_with(r"\\?\") {
+            // Verbatim paths skip normalization, so separators must already be backslashes
+            let normalized = raw.replace('/', "\\");
+            return match normalized.strip_prefix(r"\\") {
+                Some(share) => PathBuf::from(format!(r"\\?\UNC\{}", share)),
+                None => PathBuf::from(format!(r"\\?\{}", normalized)),
+            };
+        }
+    }
+
+    path.to_path_buf()
+}
+
+/// Describe an I/O error, calling out paths that are too long for the filesystem
+pub fn describe_io_error(e: &std::io::Error, path: &Path) -> String {
+    // ERROR_FILENAME_EXCED_RANGE on Windows, ENAMETOOLONG elsewhere
+    #[cfg(target_os = "windows")]
+    const NAME_TOO_LONG: i32 = 206;
+    #[cfg(target_os = "macos")]
+    const NAME_TOO_LONG: i32 = 63;
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    const NAME_TOO_LONG: i32 = 36;
+
+    if e.raw_os_error() == Some(NAME_TOO_LONG) {
+        format!("Path too long ({} characters): {}", path.as_os_str().len(), path.display())
+    } else {
+        e.to_string()
+    }
+}
+
+/// Write a file by way of a temporary sibling and a rename, so readers and crashes never
+/// see a half-written file. The temporary is removed if the write fails.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let file_name = path.file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let mut temp_name = file_name.to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    if let Err(e) = std::fs::write(long_path(&temp_path), contents) {
+        let _ = std::fs::remove_file(long_path(&temp_path));
+        return Err(describe_io_error(&e, &temp_path));
+    }
+    std::fs::rename(long_path(&temp_path), long_path(path)).map_err(|e| {
+        let _ = std::fs::remove_file(long_path(&temp_path));
+        describe_io_error(&e, path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_filename_replaces_separators_and_colons() {
+        assert_eq!(sanitize_filename("a/b\\c:d"), "a_b_c_d");
+        assert_eq!(sanitize_filename("what? <now> | \"this\" *"), "what_ _now_ _ _this_ _");
+    }
+
+    #[test]
+    fn sanitize_filename_trims_trailing_dots_and_spaces() {
+        assert_eq!(sanitize_filename("  holiday photos. . "), "holiday photos");
+        assert_eq!(sanitize_filename("..."), "");
+    }
+
+    #[test]
+    fn sanitize_filename_prefixes_reserved_names() {
+        assert_eq!(sanitize_filename("CON"), "_CON");
+        assert_eq!(sanitize_filename("nul.session"), "_nul.session");
+        assert_eq!(sanitize_filename("com1"), "_com1");
+        assert_eq!(sanitize_filename("CONSOLE"), "CONSOLE");
+    }
+
+    #[test]
+    fn sanitize_filename_truncates_on_a_char_boundary() {
+        // 'é' is two bytes, so byte MAX_FILENAME_BYTES falls in the middle of one
+        let name = format!("a{}", "é".repeat(MAX_FILENAME_BYTES));
+        let sanitized = sanitize_filename(&name);
+        assert!(sanitized.len() <= MAX_FILENAME_BYTES);
+        assert_eq!(sanitized.len(), MAX_FILENAME_BYTES - 1);
+        assert!(sanitized.chars().skip(1).all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn sanitize_filename_trims_dots_exposed_by_truncation() {
+        let name = format!("{}. tail", "x".repeat(MAX_FILENAME_BYTES - 1));
+        assert_eq!(sanitize_filename(&name), "x".repeat(MAX_FILENAME_BYTES - 1));
+    }
+
+    #[test]
+    fn canonicalize_path_folds_separator_variations() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("photo.png");
+        std::fs::write(&file, b"").unwrap();
+
+        let canonical = canonicalize_path(&file);
+        let doubled = PathBuf::from(format!("{}//photo.png", dir.path().display()));
+        let dotted = dir.path().join(".").join("photo.png");
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let via_parent = dir.path().join("sub").join("..").join("photo.png");
+
+        assert_eq!(canonicalize_path(&doubled), canonical);
+        assert_eq!(canonicalize_path(&dotted), canonical);
+        assert_eq!(canonicalize_path(&via_parent), canonical);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn canonicalize_path_folds_case_and_slashes_on_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Photo.png");
+        std::fs::write(&file, b"").unwrap();
+
+        let canonical = canonicalize_path(&file);
+        let variant = PathBuf::from(file.to_string_lossy().replace('\\', "/").to_uppercase());
+        assert_eq!(canonicalize_path(&variant), canonical);
+    }
+
+    #[test]
+    fn canonicalize_path_resolves_missing_files_through_their_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let canonical_dir = dunce::canonicalize(dir.path()).unwrap();
+
+        // A file that was just moved or trashed no longer exists, but its folder does
+        let gone = dir.path().join(".").join("moved-away.png");
+        assert_eq!(canonicalize_path(&gone), canonical_dir.join("moved-away.png"));
+    }
+
+    #[test]
+    fn canonicalize_path_keeps_paths_with_no_resolvable_parent() {
+        let path = Path::new("/nonexistent-test-dir/nested/photo.png");
+        assert_eq!(canonicalize_path(path), path);
+        assert_eq!(canonicalize_path(Path::new("photo.png")), Path::new("photo.png"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn long_path_leaves_paths_alone_off_windows() {
+        let path = PathBuf::from(format!("/{}", "a".repeat(400)));
+        assert_eq!(long_path(&path), path);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_prefixes_from_259_characters() {
+        // "C:\" plus padding, so the whole path is exactly `len` characters
+        let path_of_len = |len: usize| PathBuf::from(format!(r"C:\{}", "a".repeat(len - 3)));
+
+        assert_eq!(long_path(&path_of_len(258)), path_of_len(258));
+        let prefixed = long_path(&path_of_len(259));
+        assert_eq!(prefixed, PathBuf::from(format!(r"\\?\{}", path_of_len(259).display())));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_uses_unc_form_and_backslashes() {
+        let share = format!(r"\\server\share\{}", "a".repeat(300));
+        assert_eq!(long_path(Path::new(&share)), PathBuf::from(format!(r"\\?\UNC\server\share\{}", "a".repeat(300))));
+
+        let forward = format!("C:/{}/photo.png", "a".repeat(300));
+        assert_eq!(long_path(Path::new(&forward)), PathBuf::from(format!(r"\\?\C:\{}\photo.png", "a".repeat(300))));
+
+        // Already verbatim, and relative paths can't be made verbatim
+        let verbatim = PathBuf::from(format!(r"\\?\C:\{}", "a".repeat(300)));
+        assert_eq!(long_path(&verbatim), verbatim);
+        let relative = PathBuf::from("a".repeat(300));
+        assert_eq!(long_path(&relative), relative);
+    }
+
+    #[test]
+    fn normalize_path_lowercases_drive_letters_only() {
+        assert_eq!(normalize_path(r"C:\Photos\Trip"), "c:/Photos/Trip");
+        assert!(same_path(r"C:\Photos\Trip", "c:/Photos/Trip"));
+        assert!(!same_path("c:/Photos/Trip", "c:/photos/trip"));
+    }
+
+    #[test]
+    fn normalize_path_unifies_mixed_separators() {
+        assert_eq!(normalize_path(r"c:\Photos/Trip\day1.png"), "c:/Photos/Trip/day1.png");
+        assert!(same_path(r"\\server\share\a.png", "//server/share/a.png"));
+    }
+
+    #[test]
+    fn normalize_path_trims_trailing_separators() {
+        assert_eq!(normalize_path("/home/me/photos/"), "/home/me/photos");
+        assert_eq!(normalize_path(r"C:\Photos\\"), "c:/Photos");
+        assert!(same_path("/home/me/photos", "/home/me/photos//"));
+    }
+
+    #[test]
+    fn normalize_path_keeps_one_separator_on_roots() {
+        assert_eq!(normalize_path("/"), "/");
+        assert_eq!(normalize_path("///"), "/");
+        assert_eq!(normalize_path("c:/"), "c:/");
+        assert_eq!(normalize_path(r"C:\"), "c:/");
+        assert_eq!(normalize_path("c://"), "c:/");
+        assert!(same_path(r"C:\\", "c:/"));
+    }
+
+    #[test]
+    fn normalize_path_leaves_bare_drives_and_empty_paths() {
+        assert_eq!(normalize_path("C:"), "c:");
+        assert_eq!(normalize_path(""), "");
+        assert!(!same_path("c:", "c:/"));
+    }
+}
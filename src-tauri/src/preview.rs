@@ -0,0 +1,311 @@
+use image::codecs::gif::GifDecoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPDecoder;
+use image::imageops::FilterType;
+use image::io::Reader as ImageReader;
+use image::{AnimationDecoder, DynamicImage, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::paths::get_app_data_dir;
+use crate::settings::AppSettings;
+use crate::AppState;
+
+/// Encoding used for cached previews
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewFormat {
+    Png,
+    Jpeg,
+}
+
+impl PreviewFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.to_lowercase().as_str() {
+            "png" => Ok(Self::Png),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            _ => Err(format!("Unsupported preview format: {} (expected \"png\" or \"jpeg\")", format)),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+        }
+    }
+}
+
+/// Where and how previews are stored, resolved from the settings at the time of the request
+#[derive(Debug, Clone)]
+pub struct PreviewConfig {
+    pub dir: PathBuf,
+    pub format: PreviewFormat,
+    pub jpeg_quality: u8,
+}
+
+impl PreviewConfig {
+    pub fn from_settings(settings: &AppSettings) -> Result<Self, String> {
+        Ok(Self {
+            dir: get_preview_dir(settings.preview_dir.as_deref())?,
+            format: settings.preview_format,
+            jpeg_quality: settings.preview_jpeg_quality,
+        })
+    }
+}
+
+/// Get the directory downscaled previews are cached in (`<app data>/previews` unless overridden)
+pub fn get_preview_dir(custom_dir: Option<&str>) -> Result<PathBuf, String> {
+    match custom_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(get_app_data_dir()?.join("previews")),
+    }
+}
+
+// Helper function to name a preview after the source file's identity, so an edited file
+// (new mtime or size) or a different limit, frame or encoding gets a fresh preview instead of a stale one.
+// DefaultHasher isn't stable across Rust releases; that only costs a regeneration.
+fn preview_file_name(source: &Path, last_modified: &str, file_size: u64, limit: u32, frame_index: u32, config: &PreviewConfig) -> String {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    last_modified.hash(&mut hasher);
+    file_size.hash(&mut hasher);
+    limit.hash(&mut hasher);
+    frame_index.hash(&mut hasher);
+    config.format.hash(&mut hasher);
+    if config.format == PreviewFormat::Jpeg {
+        config.jpeg_quality.hash(&mut hasher);
+    }
+    format!("{:016x}.{}", hasher.finish(), config.format.extension())
+}
+
+// Helper function to recognise files this module wrote, so clearing a custom directory leaves other files alone
+fn is_preview_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let name = name.strip_suffix(".tmp").unwrap_or(name);
+    match name.split_once('.') {
+        Some((hash, "png" | "jpg")) => hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()),
+        _ => false,
+    }
+}
+
+/// Decode a single frame of an animated GIF or WebP; a still GIF or WebP has just frame 0
+pub fn decode_frame(path: &Path, frame_index: u32) -> Result<RgbaImage, String> {
+    let format = ImageReader::open(path)
+        .map_err(|e| format!("Failed to open image file: {}", e))?
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to detect image format: {}", e))?
+        .format();
+
+    let reader = BufReader::new(fs::File::open(path)
+        .map_err(|e| format!("Failed to open image file: {}", e))?);
+    let frames = match format {
+        Some(ImageFormat::Gif) => GifDecoder::new(reader)
+            .map_err(|e| format!("Failed to read GIF: {}", e))?
+            .into_frames(),
+        Some(ImageFormat::WebP) => WebPDecoder::new(reader)
+            .map_err(|e| format!("Failed to read WebP: {}", e))?
+            .into_frames(),
+        _ => return Err("Frame extraction is only supported for GIF and WebP images".to_string()),
+    };
+
+    // Frames are decoded in order (each is composited onto the previous ones), so walk up to the index
+    // and stop there; later frames are never decoded
+    let mut frame_count = 0;
+    for frame in frames {
+        let frame = frame.map_err(|e| format!("Failed to decode frame {}: {}", frame_count, e))?;
+        if frame_count == frame_index {
+            return Ok(frame.into_buffer());
+        }
+        frame_count += 1;
+    }
+
+    Err(format!("Frame index {} is out of range: image has {} frames", frame_index, frame_count))
+}
+
+// Helper function to decode the image a preview is made from: one frame for GIF and WebP, which may be
+// animated, and the whole image for everything else
+fn decode_preview_source(source: &Path, frame_index: u32) -> Result<DynamicImage, String> {
+    let reader = ImageReader::open(source)
+        .map_err(|e| format!("Failed to open image file: {}", e))?
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to detect image format: {}", e))?;
+
+    match reader.format() {
+        Some(ImageFormat::Gif | ImageFormat::WebP) => Ok(DynamicImage::ImageRgba8(decode_frame(source, frame_index)?)),
+        _ if frame_index > 0 => Err(format!("Frame index {} is out of range: image has 1 frame", frame_index)),
+        _ => reader.decode().map_err(|e| format!("Failed to decode image: {}", e)),
+    }
+}
+
+/// Return a preview of `source` scaled to fit within `limit` x `limit`, generating it if needed.
+/// Animated GIF and WebP files are previewed from a single frame, the first unless `frame_index` says otherwise.
+pub fn ensure_preview(source: &Path, last_modified: &str, file_size: u64, limit: u32, frame_index: u32, config: &PreviewConfig) -> Result<PathBuf, String> {
+    let preview_path = config.dir.join(preview_file_name(source, last_modified, file_size, limit, frame_index, config));
+
+    if preview_path.is_file() {
+        return Ok(preview_path);
+    }
+
+    fs::create_dir_all(&config.dir)
+        .map_err(|e| format!("Failed to create preview directory: {}", e))?;
+
+    let image = decode_preview_source(source, frame_index)?;
+    let resized = image.resize(limit, limit, FilterType::Triangle);
+
+    // Write under a temporary name so a crash never leaves a truncated preview behind
+    let temp_path = preview_path.with_extension(format!("{}.tmp", config.format.extension()));
+    match config.format {
+        PreviewFormat::Png => resized.save_with_format(&temp_path, ImageFormat::Png)
+            .map_err(|e| format!("Failed to write preview: {}", e))?,
+        PreviewFormat::Jpeg => {
+            let file = fs::File::create(&temp_path)
+                .map_err(|e| format!("Failed to write preview: {}", e))?;
+            // JPEG has no alpha channel
+            JpegEncoder::new_with_quality(BufWriter::new(file), config.jpeg_quality)
+                .encode_image(&resized.to_rgb8())
+                .map_err(|e| format!("Failed to write preview: {}", e))?;
+        }
+    }
+    fs::rename(&temp_path, &preview_path)
+        .map_err(|e| format!("Failed to store preview: {}", e))?;
+
+    println!("Generated preview for {}", source.display());
+    Ok(preview_path)
+}
+
+/// Return the preview that ships inside `source` (the JPEG embedded in a camera raw file), copying it
+/// into the preview directory on first use. It's stored as-is, whatever the configured format.
+#[cfg(feature = "raw")]
+pub fn ensure_embedded_preview(
+    source: &Path,
+    last_modified: &str,
+    file_size: u64,
+    config: &PreviewConfig,
+    extract: impl FnOnce(&Path) -> Result<Vec<u8>, String>,
+) -> Result<PathBuf, String> {
+    // Generated previews always have a limit, so 0 names the unscaled embedded copy
+    let embedded = PreviewConfig { format: PreviewFormat::Jpeg, ..config.clone() };
+    let preview_path = config.dir.join(preview_file_name(source, last_modified, file_size, 0, 0, &embedded));
+
+    if preview_path.is_file() {
+        return Ok(preview_path);
+    }
+
+    fs::create_dir_all(&config.dir)
+        .map_err(|e| format!("Failed to create preview directory: {}", e))?;
+    crate::paths::write_atomic(&preview_path, &extract(source)?)
+        .map_err(|e| format!("Failed to store preview: {}", e))?;
+
+    println!("Extracted embedded preview for {}", source.display());
+    Ok(preview_path)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewCacheSize {
+    dir: String,
+    file_count: usize,
+    total_bytes: u64,
+}
+
+// Helper function to list the preview files in the configured directory
+fn preview_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read preview directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_preview_file(path))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_preview_cache_size(state: State<'_, AppState>) -> Result<PreviewCacheSize, String> {
+    let config = PreviewConfig::from_settings(&state.settings.lock().unwrap())?;
+    let files = preview_files(&config.dir)?;
+    let total_bytes = files.iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    Ok(PreviewCacheSize {
+        dir: config.dir.to_string_lossy().to_string(),
+        file_count: files.len(),
+        total_bytes,
+    })
+}
+
+#[tauri::command]
+pub async fn clear_preview_cache(state: State<'_, AppState>) -> Result<usize, String> {
+    let config = PreviewConfig::from_settings(&state.settings.lock().unwrap())?;
+
+    let mut removed = 0;
+    for path in preview_files(&config.dir)? {
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => eprintln!("Warning: Failed to remove preview {}: {}", path.display(), e),
+        }
+    }
+
+    println!("Cleared {} previews from {}", removed, config.dir.display());
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Frame, Rgba};
+
+    // Write a two-frame GIF: solid red, then solid blue
+    fn write_animation(path: &Path) {
+        let mut encoder = GifEncoder::new(fs::File::create(path).unwrap());
+        for color in [Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])] {
+            encoder.encode_frame(Frame::new(RgbaImage::from_pixel(8, 8, color))).unwrap();
+        }
+    }
+
+    #[test]
+    fn decode_frame_picks_the_requested_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("anim.gif");
+        write_animation(&source);
+
+        assert_eq!(decode_frame(&source, 0).unwrap().get_pixel(0, 0)[0], 255);
+        assert_eq!(decode_frame(&source, 1).unwrap().get_pixel(0, 0)[2], 255);
+        assert!(decode_frame(&source, 2).unwrap_err().contains("has 2 frames"));
+    }
+
+    #[test]
+    fn ensure_preview_caches_each_frame_separately() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("anim.gif");
+        write_animation(&source);
+        let config = PreviewConfig {
+            dir: dir.path().join("previews"),
+            format: PreviewFormat::Png,
+            jpeg_quality: 85,
+        };
+
+        let first = ensure_preview(&source, "mtime", 1, 4, 0, &config).unwrap();
+        let second = ensure_preview(&source, "mtime", 1, 4, 1, &config).unwrap();
+        assert_ne!(first, second);
+
+        let poster = image::open(&first).unwrap().to_rgba8();
+        assert_eq!(poster.dimensions(), (4, 4));
+        assert_eq!(poster.get_pixel(0, 0)[0], 255);
+        assert_eq!(image::open(&second).unwrap().to_rgba8().get_pixel(0, 0)[2], 255);
+
+        // A second request is served from the cache
+        assert_eq!(ensure_preview(&source, "mtime", 1, 4, 0, &config).unwrap(), first);
+    }
+}
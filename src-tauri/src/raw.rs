@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::dimensions::{DimensionReader, ProbedImage};
+use crate::paths::{describe_io_error, long_path};
+
+/// Camera raw formats read through their TIFF structure: (extension, MIME type)
+pub const RAW_IMAGE_TYPES: &[(&str, &str)] = &[
+    ("cr2", "image/x-canon-cr2"),
+    ("nef", "image/x-nikon-nef"),
+    ("arw", "image/x-sony-arw"),
+];
+
+const TAG_IMAGE_WIDTH: u16 = 0x0100;
+const TAG_IMAGE_LENGTH: u16 = 0x0101;
+const TAG_COMPRESSION: u16 = 0x0103;
+const TAG_STRIP_OFFSETS: u16 = 0x0111;
+const TAG_STRIP_BYTE_COUNTS: u16 = 0x0117;
+const TAG_SUB_IFDS: u16 = 0x014A;
+const TAG_JPEG_OFFSET: u16 = 0x0201;
+const TAG_JPEG_LENGTH: u16 = 0x0202;
+
+// "Old-style" and regular JPEG compression; CR2 stores its full-size preview as a single JPEG strip
+const COMPRESSION_OLD_JPEG: u32 = 6;
+const COMPRESSION_JPEG: u32 = 7;
+
+// Real files have a handful of IFDs; the cap stops corrupt offset loops
+const MAX_IFDS: usize = 32;
+
+pub fn is_raw_extension(extension: &str) -> bool {
+    RAW_IMAGE_TYPES.iter().any(|(raw, _)| raw.eq_ignore_ascii_case(extension))
+}
+
+// What the IFD walk found: the largest declared image size and every JPEG-looking blob (offset, length)
+#[derive(Default)]
+struct RawLayout {
+    largest_dimensions: Option<(u32, u32)>,
+    jpeg_candidates: Vec<(u64, u64)>,
+}
+
+struct TiffReader {
+    reader: BufReader<File>,
+    little_endian: bool,
+}
+
+impl TiffReader {
+    fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(long_path(path))
+            .map_err(|e| format!("Failed to open raw file: {}", describe_io_error(&e, path)))?;
+        let mut reader = BufReader::new(file);
+
+        let mut byte_order = [0u8; 2];
+        reader.read_exact(&mut byte_order)
+            .map_err(|e| format!("Failed to read raw file header: {}", e))?;
+        let little_endian = match &byte_order {
+            b"II" => true,
+            b"MM" => false,
+            _ => return Err(format!("Not a TIFF-based raw file: {}", path.display())),
+        };
+
+        Ok(Self { reader, little_endian })
+    }
+
+    fn read_u16(&mut self, offset: u64) -> io::Result<u16> {
+        let mut bytes = [0u8; 2];
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(&mut bytes)?;
+        Ok(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    fn read_u32(&mut self, offset: u64) -> io::Result<u32> {
+        let mut bytes = [0u8; 4];
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(&mut bytes)?;
+        Ok(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    // Helper function to read the first value of an IFD entry; SHORT values sit in the entry's first two bytes
+    fn read_entry_value(&mut self, entry_offset: u64) -> io::Result<u32> {
+        const TYPE_SHORT: u16 = 3;
+        if self.read_u16(entry_offset + 2)? == TYPE_SHORT {
+            return self.read_u16(entry_offset + 8).map(u32::from);
+        }
+        self.read_u32(entry_offset + 8)
+    }
+
+    // Helper function to read the offsets listed by a SubIFDs entry (inline when there's only one)
+    fn read_sub_ifd_offsets(&mut self, entry_offset: u64) -> io::Result<Vec<u64>> {
+        let count = self.read_u32(entry_offset + 4)?.min(MAX_IFDS as u32);
+        if count <= 1 {
+            return Ok(vec![self.read_u32(entry_offset + 8)? as u64]);
+        }
+        let array_offset = self.read_u32(entry_offset + 8)? as u64;
+        (0..count as u64)
+            .map(|i| self.read_u32(array_offset + i * 4).map(u64::from))
+            .collect()
+    }
+
+    // Walk IFD0's chain and every SubIFD, noting image sizes and embedded JPEGs
+    fn read_layout(&mut self) -> io::Result<RawLayout> {
+        let mut layout = RawLayout::default();
+        let mut pending = vec![self.read_u32(4)? as u64];
+        let mut visited = HashSet::new();
+
+        while let Some(ifd_offset) = pending.pop() {
+            if ifd_offset == 0 || visited.len() >= MAX_IFDS || !visited.insert(ifd_offset) {
+                continue;
+            }
+
+            let entry_count = self.read_u16(ifd_offset)? as u64;
+            let (mut width, mut height, mut compression) = (0, 0, 0);
+            let (mut strip_offset, mut strip_length) = (None, None);
+            let (mut jpeg_offset, mut jpeg_length) = (None, None);
+
+            for i in 0..entry_count {
+                let entry_offset = ifd_offset + 2 + i * 12;
+                match self.read_u16(entry_offset)? {
+                    TAG_IMAGE_WIDTH => width = self.read_entry_value(entry_offset)?,
+                    TAG_IMAGE_LENGTH => height = self.read_entry_value(entry_offset)?,
+                    TAG_COMPRESSION => compression = self.read_entry_value(entry_offset)?,
+                    // Only single-strip images can be a standalone JPEG
+                    TAG_STRIP_OFFSETS if self.read_u32(entry_offset + 4)? == 1 => strip_offset = Some(self.read_entry_value(entry_offset)?),
+                    TAG_STRIP_BYTE_COUNTS if self.read_u32(entry_offset + 4)? == 1 => strip_length = Some(self.read_entry_value(entry_offset)?),
+                    TAG_JPEG_OFFSET => jpeg_offset = Some(self.read_entry_value(entry_offset)?),
+                    TAG_JPEG_LENGTH => jpeg_length = Some(self.read_entry_value(entry_offset)?),
+                    TAG_SUB_IFDS => pending.extend(self.read_sub_ifd_offsets(entry_offset)?),
+                    _ => {}
+                }
+            }
+            pending.push(self.read_u32(ifd_offset + 2 + entry_count * 12)? as u64);
+
+            if width as u64 * height as u64 > layout.largest_dimensions.map(|(w, h)| w as u64 * h as u64).unwrap_or(0) {
+                layout.largest_dimensions = Some((width, height));
+            }
+            if let (Some(offset), Some(length)) = (jpeg_offset, jpeg_length) {
+                layout.jpeg_candidates.push((offset as u64, length as u64));
+            }
+            if matches!(compression, COMPRESSION_OLD_JPEG | COMPRESSION_JPEG) {
+                if let (Some(offset), Some(length)) = (strip_offset, strip_length) {
+                    layout.jpeg_candidates.push((offset as u64, length as u64));
+                }
+            }
+        }
+
+        Ok(layout)
+    }
+
+    fn read_bytes(&mut self, offset: u64, length: u64) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.reader.seek(SeekFrom::Start(offset))?;
+        (&mut self.reader).take(length).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+// Helper function to check a blob is a JPEG a browser can show. Raw sensor data is also stored as
+// "JPEG" (lossless, SOF3), which has to be skipped.
+fn is_displayable_jpeg(bytes: &[u8]) -> bool {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() && bytes[pos] == 0xFF {
+        let marker = bytes[pos + 1];
+        if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            // Baseline, extended sequential and progressive are the web-viewable frame types
+            return matches!(marker, 0xC0..=0xC2);
+        }
+        let segment_length = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        pos += 2 + segment_length;
+    }
+    false
+}
+
+// Helper function to pull the largest displayable embedded JPEG out of a raw file
+fn largest_preview(reader: &mut TiffReader, layout: &RawLayout) -> Result<Option<Vec<u8>>, String> {
+    let mut candidates = layout.jpeg_candidates.clone();
+    candidates.sort_by_key(|&(_, length)| std::cmp::Reverse(length));
+
+    for (offset, length) in candidates {
+        let bytes = reader.read_bytes(offset, length)
+            .map_err(|e| format!("Failed to read embedded preview: {}", e))?;
+        if is_displayable_jpeg(&bytes) {
+            return Ok(Some(bytes));
+        }
+    }
+    Ok(None)
+}
+
+/// Read a raw file's dimensions from its metadata. The largest image declared in any IFD is the
+/// sensor image; if none declares a size, the embedded preview's size is used instead.
+pub fn read_dimensions(path: &Path) -> Result<(u32, u32), String> {
+    let mut reader = TiffReader::open(path)?;
+    let layout = reader.read_layout()
+        .map_err(|e| format!("Failed to read raw file structure: {}", e))?;
+
+    if let Some(dimensions) = layout.largest_dimensions {
+        return Ok(dimensions);
+    }
+
+    let preview = largest_preview(&mut reader, &layout)?
+        .ok_or_else(|| format!("NoPreview: {} has no embedded preview to measure", path.display()))?;
+    image::io::Reader::with_format(Cursor::new(preview), image::ImageFormat::Jpeg)
+        .into_dimensions()
+        .map_err(|e| format!("Failed to read embedded preview dimensions: {}", e))
+}
+
+/// Dimension reader for camera raw files, registered for every extension in RAW_IMAGE_TYPES
+pub struct RawDimensionReader;
+
+impl DimensionReader for RawDimensionReader {
+    fn read_dimensions(&self, path: &Path) -> Result<ProbedImage, String> {
+        let (width, height) = read_dimensions(path)?;
+        Ok(ProbedImage { width, height, format: None })
+    }
+}
+
+/// Extract the largest embedded JPEG preview of a raw file, for display without a raw develop
+pub fn extract_preview(path: &Path) -> Result<Vec<u8>, String> {
+    let mut reader = TiffReader::open(path)?;
+    let layout = reader.read_layout()
+        .map_err(|e| format!("Failed to read raw file structure: {}", e))?;
+
+    largest_preview(&mut reader, &layout)?
+        .ok_or_else(|| format!("NoPreview: {} has no embedded preview", path.display()))
+}
@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::paths::normalize_path;
+use crate::{collect_image_files, SessionData, SessionTab};
+
+// Fewer missing tabs than this is ordinary churn rather than a relocated session
+const DRIFT_MIN_MISSING: usize = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RebasedSession {
+    #[serde(rename = "sessionData")]
+    session_data: SessionData,
+    #[serde(rename = "changedCount")]
+    changed_count: usize,
+}
+
+// Suggested prefix rewrite for a session whose images appear to have moved
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PathDriftSuggestion {
+    #[serde(rename = "missingCount")]
+    missing_count: usize,
+    #[serde(rename = "totalCount")]
+    total_count: usize,
+    #[serde(rename = "oldPrefix")]
+    old_prefix: String,
+    #[serde(rename = "newPrefix")]
+    new_prefix: Option<String>, // Only set when a swapped drive letter was found to hold the images
+}
+
+// Images gained and lost by a folder since a session was saved from it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderSessionDiff {
+    added: Vec<String>, // Folder images no tab refers to
+    removed: Vec<String>, // Tab images that are no longer in the folder
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResyncedSession {
+    #[serde(rename = "sessionData")]
+    session_data: SessionData,
+    #[serde(rename = "addedCount")]
+    added_count: usize,
+    #[serde(rename = "missingTabIds")]
+    missing_tab_ids: Vec<String>, // Tabs whose image left the folder; already dropped if remove_missing was set
+}
+
+// Helper function to list a folder's images for comparison against a session
+fn folder_image_paths(folder: &str) -> Result<Vec<String>, String> {
+    let folder_path = Path::new(folder);
+    if !folder_path.is_dir() {
+        return Err(format!("Path is not a directory: {}", folder));
+    }
+
+    Ok(collect_image_files(folder_path)?
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect())
+}
+
+// Helper function to replace `old_prefix` at the start of `path`, only on a path component boundary
+fn rebase_path(path: &str, old_prefix: &str, new_prefix: &str) -> Option<String> {
+    let rest = path.strip_prefix(old_prefix)?;
+    let at_boundary = rest.is_empty()
+        || old_prefix.ends_with(['/', '\\'])
+        || rest.starts_with(['/', '\\']);
+    at_boundary.then(|| format!("{}{}", new_prefix, rest))
+}
+
+// Helper function to find the longest directory prefix shared by all paths, keeping the original separators
+fn common_directory_prefix(paths: &[&str]) -> Option<String> {
+    let first = paths.first()?;
+    let mut prefix = &first[..first.rfind(['/', '\\'])?];
+
+    for path in &paths[1..] {
+        // Drop trailing components until this path also lives under the prefix
+        while !path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with(['/', '\\'])) {
+            prefix = &prefix[..prefix.rfind(['/', '\\'])?];
+        }
+    }
+
+    // A bare root or drive ("", "C:") says nothing useful about where the images went
+    if prefix.trim_end_matches(['/', '\\', ':']).len() <= 1 {
+        return None;
+    }
+    Some(prefix.to_string())
+}
+
+// Helper function to try the same prefix on other drive letters, returning the first that has the missing file
+fn find_drive_letter_swap(old_prefix: &str, sample_path: &str) -> Option<String> {
+    let mut chars = old_prefix.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    if chars.next() != Some(':') {
+        return None;
+    }
+
+    ('A'..='Z')
+        .filter(|letter| !letter.eq_ignore_ascii_case(&drive))
+        .map(|letter| format!("{}{}", letter, &old_prefix[1..]))
+        .find(|candidate| {
+            rebase_path(sample_path, old_prefix, candidate)
+                .map(|rebased| Path::new(&rebased).exists())
+                .unwrap_or(false)
+        })
+}
+
+// Helper function to spot sessions whose images mostly went missing under one shared folder
+pub fn detect_path_drift(session_data: &SessionData) -> Option<PathDriftSuggestion> {
+    let missing: Vec<&str> = session_data.tabs.iter()
+        .map(|tab| tab.image_path.as_str())
+        .filter(|path| !Path::new(path).exists())
+        .collect();
+
+    let total_count = session_data.tabs.len();
+    if missing.len() < DRIFT_MIN_MISSING || missing.len() * 2 < total_count {
+        return None;
+    }
+
+    let old_prefix = common_directory_prefix(&missing)?;
+    let new_prefix = find_drive_letter_swap(&old_prefix, missing[0]);
+    Some(PathDriftSuggestion {
+        missing_count: missing.len(),
+        total_count,
+        old_prefix,
+        new_prefix,
+    })
+}
+
+// Helper function to log a hint when a freshly loaded session looks relocated
+pub fn warn_on_path_drift(session_data: &SessionData) {
+    if let Some(drift) = detect_path_drift(session_data) {
+        eprintln!(
+            "Warning: {} of {} session images are missing under {}{}",
+            drift.missing_count,
+            drift.total_count,
+            drift.old_prefix,
+            drift.new_prefix.map(|p| format!(" (found at {})", p)).unwrap_or_default(),
+        );
+    }
+}
+
+#[tauri::command]
+pub async fn rebase_session_paths(mut session_data: SessionData, old_prefix: String, new_prefix: String) -> Result<RebasedSession, String> {
+    if old_prefix.is_empty() {
+        return Err("Old prefix must not be empty".to_string());
+    }
+
+    let mut changed_count = 0;
+    for tab in &mut session_data.tabs {
+        if let Some(rebased) = rebase_path(&tab.image_path, &old_prefix, &new_prefix) {
+            tab.image_path = rebased;
+            changed_count += 1;
+        }
+    }
+
+    println!("Rebased {} session image paths from {} to {}", changed_count, old_prefix, new_prefix);
+    Ok(RebasedSession {
+        session_data,
+        changed_count,
+    })
+}
+
+#[tauri::command]
+pub async fn diff_folder_against_session(folder: String, session_data: SessionData) -> Result<FolderSessionDiff, String> {
+    let folder_images = folder_image_paths(&folder)?;
+    let in_folder: HashSet<String> = folder_images.iter().map(|path| normalize_path(path)).collect();
+    let in_session: HashSet<String> = session_data.tabs.iter().map(|tab| normalize_path(&tab.image_path)).collect();
+
+    let added = folder_images.into_iter()
+        .filter(|path| !in_session.contains(&normalize_path(path)))
+        .collect();
+
+    // A path open in several tabs is reported once
+    let mut reported = HashSet::new();
+    let removed = session_data.tabs.into_iter()
+        .map(|tab| tab.image_path)
+        .filter(|path| {
+            let normalized = normalize_path(path);
+            !in_folder.contains(&normalized) && reported.insert(normalized)
+        })
+        .collect();
+
+    Ok(FolderSessionDiff { added, removed })
+}
+
+// Bring a session saved from a folder up to date: new folder images become ungrouped tabs at the
+// end, and tabs whose image is gone are reported (and dropped if `remove_missing` is set).
+// Existing tabs keep their ids, view state and groups.
+#[tauri::command]
+pub async fn resync_session_with_folder(folder: String, mut session_data: SessionData, remove_missing: Option<bool>) -> Result<ResyncedSession, String> {
+    let folder_images = folder_image_paths(&folder)?;
+    let in_folder: HashSet<String> = folder_images.iter().map(|path| normalize_path(path)).collect();
+    let in_session: HashSet<String> = session_data.tabs.iter().map(|tab| normalize_path(&tab.image_path)).collect();
+
+    let missing_tab_ids: Vec<String> = session_data.tabs.iter()
+        .filter(|tab| !in_folder.contains(&normalize_path(&tab.image_path)))
+        .map(|tab| tab.id.clone())
+        .collect();
+
+    if remove_missing.unwrap_or(false) && !missing_tab_ids.is_empty() {
+        session_data.tabs.retain(|tab| !missing_tab_ids.contains(&tab.id));
+        let active_removed = session_data.active_tab_id.as_ref().is_some_and(|id| missing_tab_ids.contains(id));
+        if active_removed {
+            session_data.active_tab_id = session_data.tabs.first().map(|tab| tab.id.clone());
+        }
+    }
+
+    let mut next_order = session_data.tabs.iter().map(|tab| tab.order + 1).max().unwrap_or(0);
+    let new_tabs: Vec<SessionTab> = folder_images.into_iter()
+        .filter(|path| !in_session.contains(&normalize_path(path)))
+        .map(|image_path| {
+            let order = next_order;
+            next_order += 1;
+            SessionTab {
+                id: Uuid::new_v4().to_string(),
+                image_path,
+                order,
+                group_id: None,
+                zoom_level: None,
+                fit_mode: None,
+                pan_offset: None,
+            }
+        })
+        .collect();
+    let added_count = new_tabs.len();
+    session_data.tabs.extend(new_tabs);
+    if session_data.active_tab_id.is_none() {
+        session_data.active_tab_id = session_data.tabs.first().map(|tab| tab.id.clone());
+    }
+
+    println!("Resynced session with {}: {} tabs added, {} missing", folder, added_count, missing_tab_ids.len());
+    Ok(ResyncedSession {
+        session_data,
+        added_count,
+        missing_tab_ids,
+    })
+}
+
+#[tauri::command]
+pub async fn detect_session_path_drift(session_data: SessionData) -> Result<Option<PathDriftSuggestion>, String> {
+    Ok(detect_path_drift(&session_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(paths: &[String]) -> SessionData {
+        let tabs: Vec<_> = paths.iter().enumerate()
+            .map(|(order, path)| serde_json::json!({ "id": order.to_string(), "imagePath": path, "order": order }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "name": null,
+            "tabs": tabs,
+            "activeTabId": null,
+            "createdAt": "2024-01-01T00:00:00Z",
+        })).unwrap()
+    }
+
+    #[test]
+    fn rebase_path_only_matches_whole_components() {
+        assert_eq!(rebase_path("/photos/a.png", "/photos", "/pictures").as_deref(), Some("/pictures/a.png"));
+        assert_eq!(rebase_path("/photos-old/a.png", "/photos", "/pictures"), None);
+        assert_eq!(rebase_path("/photos", "/photos", "/pictures").as_deref(), Some("/pictures"));
+        assert_eq!(rebase_path("/other/a.png", "/photos", "/pictures"), None);
+    }
+
+    #[test]
+    fn rebase_path_accepts_trailing_separators_and_mixed_styles() {
+        assert_eq!(rebase_path("/photos/a.png", "/photos/", "/pictures/").as_deref(), Some("/pictures/a.png"));
+        assert_eq!(rebase_path("C:\\photos/a.png", "C:\\photos", "D:\\photos").as_deref(), Some("D:\\photos/a.png"));
+        assert_eq!(rebase_path("C:\\photos\\a.png", "C:\\", "E:\\").as_deref(), Some("E:\\photos\\a.png"));
+    }
+
+    #[test]
+    fn common_directory_prefix_stops_at_component_boundaries() {
+        assert_eq!(
+            common_directory_prefix(&["/photos/trip/a.png", "/photos/trip-2/b.png"]).as_deref(),
+            Some("/photos"),
+        );
+        // Prefixes are compared as written, so a component is only shared up to the first differing separator;
+        // the result still rebases both paths since rebase_path matches the same way
+        assert_eq!(
+            common_directory_prefix(&["C:\\photos\\trip\\a.png", "C:\\photos/trip/b.png"]).as_deref(),
+            Some("C:\\photos"),
+        );
+        assert_eq!(common_directory_prefix(&["/photos/a.png"]).as_deref(), Some("/photos"));
+    }
+
+    #[test]
+    fn common_directory_prefix_ignores_bare_roots_and_drives() {
+        assert_eq!(common_directory_prefix(&["C:\\pics\\a.png", "D:\\pics\\b.png"]), None);
+        assert_eq!(common_directory_prefix(&["C:\\a.png", "C:\\b.png"]), None);
+        assert_eq!(common_directory_prefix(&["/a.png", "/b.png"]), None);
+        assert_eq!(common_directory_prefix(&[]), None);
+    }
+
+    #[test]
+    fn detect_path_drift_needs_enough_missing_tabs() {
+        let dir = tempfile::tempdir().unwrap();
+        let present = dir.path().join("present.png");
+        std::fs::write(&present, b"").unwrap();
+        let present = present.to_string_lossy().to_string();
+        let moved = |name: &str| dir.path().join("moved").join(name).to_string_lossy().to_string();
+
+        // Below DRIFT_MIN_MISSING
+        assert!(detect_path_drift(&session(&[moved("a.png"), present.clone()])).is_none());
+
+        // Enough missing, but under half the tabs
+        let mostly_present = [moved("a.png"), moved("b.png"), present.clone(), present.clone(), present.clone()];
+        assert!(detect_path_drift(&session(&mostly_present)).is_none());
+
+        let drift = detect_path_drift(&session(&[moved("a.png"), moved("b.png"), present])).unwrap();
+        assert_eq!((drift.missing_count, drift.total_count), (2, 3));
+        assert_eq!(drift.old_prefix, dir.path().join("moved").to_string_lossy());
+        assert_eq!(drift.new_prefix, None);
+    }
+}
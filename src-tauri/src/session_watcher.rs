@@ -0,0 +1,83 @@
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// Editors often write a file several times per save; changes within this window are reported once
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+/// Changes this soon after the app wrote the session file itself are not reported
+const SELF_WRITE_GRACE: Duration = Duration::from_secs(2);
+
+/// Watches the currently loaded session file and emits `loaded-session-file-changed`
+/// when it is modified outside the app
+pub struct SessionWatcher {
+    debouncer: Mutex<Option<Debouncer<RecommendedWatcher>>>,
+    last_self_write: Arc<Mutex<Option<Instant>>>,
+}
+
+impl SessionWatcher {
+    pub fn new() -> Self {
+        Self {
+            debouncer: Mutex::new(None),
+            last_self_write: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start watching a session file, replacing any previous watch
+    pub fn watch(&self, app: &tauri::AppHandle, session_path: &str) -> Result<(), String> {
+        self.stop();
+
+        let session_path = PathBuf::from(session_path);
+
+        // Watch the parent directory: editors that save atomically replace the file,
+        // which would silently end a watch placed on the file itself
+        let watch_dir = session_path.parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .ok_or_else(|| format!("Invalid session path: {}", session_path.display()))?;
+
+        let app = app.clone();
+        let last_self_write = self.last_self_write.clone();
+        let watched_path = session_path.clone();
+
+        let mut debouncer = new_debouncer(DEBOUNCE_DELAY, move |result: DebounceEventResult| {
+            let Ok(events) = result else {
+                return;
+            };
+
+            if !events.iter().any(|event| event.path == watched_path) {
+                return;
+            }
+
+            let recently_saved = last_self_write.lock().unwrap()
+                .map(|time| time.elapsed() < SELF_WRITE_GRACE)
+                .unwrap_or(false);
+            if recently_saved {
+                return;
+            }
+
+            println!("Loaded session file changed on disk: {}", watched_path.display());
+            let _ = app.emit("loaded-session-file-changed", watched_path.to_string_lossy().to_string());
+        }).map_err(|e| format!("Failed to create session file watcher: {}", e))?;
+
+        debouncer.watcher()
+            .watch(&watch_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch session file: {}", e))?;
+
+        *self.debouncer.lock().unwrap() = Some(debouncer);
+        Ok(())
+    }
+
+    /// Stop watching (dropping the debouncer ends the watch)
+    pub fn stop(&self) {
+        self.debouncer.lock().unwrap().take();
+    }
+
+    /// Record that the app itself is about to write the session file
+    pub fn mark_self_write(&self) {
+        *self.last_self_write.lock().unwrap() = Some(Instant::now());
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::metadata_cache::DEFAULT_EVICTION_TARGET_RATIO;
+use crate::paths::get_app_data_dir;
+use crate::preview::PreviewFormat;
+
+/// User preferences persisted in `settings.json` in the app data directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Directory the open dialogs were last used in
+    pub last_dialog_dir: Option<String>,
+    /// Folder most recently opened through browse_folder
+    pub last_folder: Option<String>,
+    /// Whether to reopen `last_folder` on launch
+    pub reopen_last_folder: bool,
+    /// Page size used by browse_folder_paginated when no limit is given
+    pub default_page_size: usize,
+    /// Keep the viewer windows above other applications
+    pub always_on_top: bool,
+    /// Window title template; `{session}` and `{image}` are substituted
+    pub title_template: String,
+    /// Maximum number of rows kept in the metadata cache
+    pub cache_max_entries: usize,
+    /// Once over the limit, the cache is evicted down to this fraction of it
+    pub cache_eviction_target_ratio: f64,
+    /// Mark images as seen whenever they are opened in the viewer
+    pub auto_mark_seen: bool,
+    /// Images with more pixels than this are flagged as `large_image` in ImageData
+    pub large_image_pixel_threshold: u64,
+    /// Images wider or taller than this many pixels also get a downscaled preview; 0 disables previews
+    pub oversize_limit: u32,
+    /// Custom location for cached previews; `<app data>/previews` when unset
+    pub preview_dir: Option<String>,
+    /// Encoding for newly generated previews
+    pub preview_format: PreviewFormat,
+    /// JPEG quality (1-100) used when `preview_format` is JPEG
+    pub preview_jpeg_quality: u8,
+    /// Custom location for the metadata cache database; the app data directory when unset.
+    /// Read once at startup.
+    pub cache_db_path: Option<String>,
+    /// How many decode-heavy operations may run at once; 0 uses the CPU count. Read once at startup.
+    pub max_concurrent_decodes: usize,
+    /// Pre-read metadata for the last folder and autosaved session in the background after launch
+    pub warm_cache_on_startup: bool,
+    /// Seconds to wait for the frontend's exit save (from begin_exit_save) before the process is force-exited; 0 waits forever
+    pub exit_timeout_secs: u64,
+    /// Collapsed state of the frontend's session menu sections, by section id; sections not listed
+    /// are expanded. Native menus can't collapse submenus, so only the frontend reads this.
+    pub menu_sections_collapsed: HashMap<String, bool>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            last_dialog_dir: None,
+            last_folder: None,
+            reopen_last_folder: false,
+            default_page_size: 500,
+            always_on_top: false,
+            title_template: "Image Viewer: {session}".to_string(),
+            cache_max_entries: 100_000,
+            cache_eviction_target_ratio: DEFAULT_EVICTION_TARGET_RATIO,
+            auto_mark_seen: false,
+            large_image_pixel_threshold: 100_000_000,
+            oversize_limit: 8192,
+            preview_dir: None,
+            preview_format: PreviewFormat::Png,
+            preview_jpeg_quality: 85,
+            cache_db_path: None,
+            max_concurrent_decodes: 0,
+            warm_cache_on_startup: false,
+            exit_timeout_secs: 5,
+            menu_sections_collapsed: HashMap::new(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Load settings from disk, falling back to defaults if the file is missing or invalid
+    pub fn load() -> Self {
+        let settings_file = match Self::get_settings_path() {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("Failed to resolve settings path: {}", e);
+                return Self::default();
+            }
+        };
+
+        if !settings_file.exists() {
+            return Self::default();
+        }
+
+        match fs::read_to_string(&settings_file) {
+            Ok(json_data) => match serde_json::from_str(&json_data) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    eprintln!("Failed to parse settings: {}", e);
+                    Self::default()
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to read settings file: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the settings to disk
+    pub fn save(&self) -> Result<(), String> {
+        let settings_file = Self::get_settings_path()?;
+
+        if let Some(parent) = settings_file.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        }
+
+        let json_data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+        fs::write(&settings_file, json_data)
+            .map_err(|e| format!("Failed to write settings file: {}", e))
+    }
+
+    fn get_settings_path() -> Result<PathBuf, String> {
+        Ok(get_app_data_dir()?.join("settings.json"))
+    }
+}
+
+/// Apply a change to the shared settings and persist the result
+pub fn update_settings<F>(settings: &Mutex<AppSettings>, change: F) -> Result<(), String>
+where
+    F: FnOnce(&mut AppSettings),
+{
+    let mut settings = settings.lock().unwrap();
+    change(&mut settings);
+    settings.save()
+}
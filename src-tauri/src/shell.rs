@@ -0,0 +1,57 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Open a directory in the platform's file manager
+pub fn open_in_file_manager(path: &Path) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut command = Command::new("explorer");
+
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+
+    command
+        .arg(path)
+        .spawn()
+        .map_err(|e| format!("Failed to open file manager: {}", e))?;
+
+    Ok(())
+}
+
+/// Send a file to the platform's default printer
+///
+/// On Windows this goes through the shell "print" verb, which for images opens the
+/// system Print Pictures dialog. Elsewhere the file is queued with CUPS via `lp`.
+pub fn print_file(path: &Path) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command"])
+            .arg("Start-Process -FilePath $args[0] -Verb Print")
+            .arg(path);
+        command
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let mut command = {
+        let mut command = Command::new("lp");
+        command.arg(path);
+        command
+    };
+
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to start print command: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Print command failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::path::{Path, MAIN_SEPARATOR};
+use tauri::State;
+
+use crate::paths::canonicalize_path;
+use crate::AppState;
+
+// Helper function to trim tags and drop empty or duplicate ones
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = tags
+        .into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+#[tauri::command]
+pub async fn set_image_tags(path: String, tags: Vec<String>, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    if !Path::new(&path).is_file() {
+        return Err(format!("Image file does not exist: {}", path));
+    }
+
+    let tags = normalize_tags(tags);
+    state.metadata_cache.set_tags(&path, &tags)?;
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn get_image_tags(path: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    state.metadata_cache.get_tags(&path)
+}
+
+#[tauri::command]
+pub async fn find_images_by_tag(path: String, tag: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+
+    // Tags are keyed by canonical path. Match on a trailing separator so "/photos"
+    // doesn't also pick up "/photos-old"
+    let mut prefix = canonicalize_path(Path::new(&path)).to_string_lossy().to_string();
+    if !prefix.ends_with(MAIN_SEPARATOR) {
+        prefix.push(MAIN_SEPARATOR);
+    }
+
+    let paths = state.metadata_cache.find_by_tag(&prefix, tag.trim())?;
+
+    // Tagged files may have been removed outside the app
+    Ok(paths.into_iter().filter(|p| Path::new(p).is_file()).collect())
+}
+
+/// Highest star rating an image can have
+const MAX_RATING: u8 = 5;
+
+#[tauri::command]
+pub async fn set_rating(path: String, stars: u8, state: State<'_, AppState>) -> Result<(), String> {
+    if stars > MAX_RATING {
+        return Err(format!("Rating must be between 0 and {}", MAX_RATING));
+    }
+
+    if !Path::new(&path).is_file() {
+        return Err(format!("Image file does not exist: {}", path));
+    }
+
+    state.metadata_cache.set_rating(&path, stars)
+}
+
+#[tauri::command]
+pub async fn get_rating(path: String, state: State<'_, AppState>) -> Result<u8, String> {
+    state.metadata_cache.get_rating(&path)
+}
+
+#[tauri::command]
+pub async fn mark_seen(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.metadata_cache.mark_seen(&path)
+}
+
+#[tauri::command]
+pub async fn is_seen(path: String, state: State<'_, AppState>) -> Result<bool, String> {
+    state.metadata_cache.is_seen(&path)
+}
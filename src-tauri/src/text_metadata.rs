@@ -0,0 +1,143 @@
+use image::io::Reader as ImageReader;
+use image::ImageFormat;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use tauri::State;
+
+use crate::path_allowlist::is_path_allowed;
+use crate::AppState;
+
+/// Key used for JPEG COM segments, which carry no key of their own
+const JPEG_COMMENT_KEY: &str = "Comment";
+
+/// Key used for the EXIF UserComment tag
+const EXIF_USER_COMMENT_KEY: &str = "UserComment";
+
+// Helper function to collect tEXt, zTXt and iTXt chunks from a PNG, including ones after the image data
+fn read_png_text(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open image file: {}", e))?;
+    let mut reader = png::Decoder::new(BufReader::new(file))
+        .read_info()
+        .map_err(|e| format!("Failed to read PNG: {}", e))?;
+
+    // Skips the pixel data and parses the trailing chunks
+    reader.finish().map_err(|e| format!("Failed to read PNG: {}", e))?;
+
+    let info = reader.info();
+    let mut entries: Vec<(String, String)> = info.uncompressed_latin1_text.iter()
+        .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+        .collect();
+    for chunk in &info.compressed_latin1_text {
+        match chunk.get_text() {
+            Ok(text) => entries.push((chunk.keyword.clone(), text)),
+            Err(e) => eprintln!("Skipping unreadable zTXt chunk '{}': {}", chunk.keyword, e),
+        }
+    }
+    for chunk in &info.utf8_text {
+        match chunk.get_text() {
+            Ok(text) => entries.push((chunk.keyword.clone(), text)),
+            Err(e) => eprintln!("Skipping unreadable iTXt chunk '{}': {}", chunk.keyword, e),
+        }
+    }
+
+    Ok(entries)
+}
+
+// Helper function to collect COM segments from a JPEG. Stops at the start of the scan data,
+// since comments appear in the header.
+fn read_jpeg_comments(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open image file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut comments = Vec::new();
+
+    let mut marker = [0u8; 2];
+    reader.read_exact(&mut marker).map_err(|e| format!("Failed to read JPEG: {}", e))?;
+    if marker != [0xFF, 0xD8] {
+        return Err("Not a JPEG file".to_string());
+    }
+
+    loop {
+        if reader.read_exact(&mut marker).is_err() || marker[0] != 0xFF {
+            break;
+        }
+        // Start of scan or end of image: no more header segments
+        if marker[1] == 0xDA || marker[1] == 0xD9 {
+            break;
+        }
+
+        let mut length = [0u8; 2];
+        reader.read_exact(&mut length).map_err(|e| format!("Failed to read JPEG: {}", e))?;
+        let length = (u16::from_be_bytes(length) as usize).saturating_sub(2);
+
+        let mut segment = vec![0u8; length];
+        reader.read_exact(&mut segment).map_err(|e| format!("Failed to read JPEG: {}", e))?;
+
+        if marker[1] == 0xFE {
+            let text = String::from_utf8_lossy(&segment).trim_end_matches('\0').to_string();
+            comments.push((JPEG_COMMENT_KEY.to_string(), text));
+        }
+    }
+
+    Ok(comments)
+}
+
+// Helper function to read the EXIF UserComment, whose first 8 bytes name the character code
+fn read_exif_user_comment(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+    let field = exif.get_field(exif::Tag::UserComment, exif::In::PRIMARY)?;
+
+    let exif::Value::Undefined(bytes, _) = &field.value else {
+        return None;
+    };
+    let (charset, body) = bytes.split_at(bytes.len().min(8));
+
+    let text = if charset.starts_with(b"UNICODE") {
+        let units: Vec<u16> = body
+            .chunks_exact(2)
+            .map(|pair| if exif.little_endian() {
+                u16::from_le_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_be_bytes([pair[0], pair[1]])
+            })
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        // ASCII, JIS and undefined are all treated as (lossy) UTF-8
+        String::from_utf8_lossy(body).to_string()
+    };
+
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[tauri::command]
+pub async fn get_text_metadata(path: String, state: State<'_, AppState>) -> Result<Vec<(String, String)>, String> {
+    is_path_allowed(&state, &path)?;
+    let image_path = Path::new(&path);
+
+    if !image_path.is_file() {
+        return Err(format!("Image file does not exist: {}", path));
+    }
+
+    let format = ImageReader::open(image_path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| format!("Failed to detect image format: {}", e))?
+        .format();
+
+    match format {
+        Some(ImageFormat::Png) => read_png_text(image_path),
+        Some(ImageFormat::Jpeg) => {
+            let mut entries = read_jpeg_comments(image_path)?;
+            if let Some(comment) = read_exif_user_comment(image_path) {
+                entries.push((EXIF_USER_COMMENT_KEY.to_string(), comment));
+            }
+            Ok(entries)
+        }
+        // Other formats have no textual metadata we understand
+        _ => Ok(Vec::new()),
+    }
+}
@@ -11,8 +11,8 @@ export class TauriSessionService implements SessionService {
    */
   async saveSessionDialog(sessionData: SessionData): Promise<string | null> {
     try {
-      const result = await invoke<string | null>('save_session_dialog', { sessionData })
-      return result
+      const result = await invoke<{ path: string; bytesWritten: number } | null>('save_session_dialog', { sessionData })
+      return result?.path ?? null
     } catch (error) {
       console.error('Failed to save session via dialog:', error)
       throw new Error(`Failed to save session: ${error}`)